@@ -9,49 +9,488 @@ use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use raw_window_handle::{XcbDisplayHandle, XcbWindowHandle};
 
 #[cfg(target_os = "linux")]
-use xcb::{x, Xid};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet}, ffi::{c_char, c_long, c_ulong, CStr}, ptr,
+    sync::{Mutex, Once, OnceLock, PoisonError, TryLockError},
+};
+
+#[cfg(target_os = "linux")]
+use xcb::{randr, x, xinput, BaseEvent, Xid, XidNew};
 
 #[cfg(target_os = "windows")]
 use raw_window_handle::{Win32WindowHandle, WindowsDisplayHandle};
 
 #[cfg(target_os = "windows")]
-use std::{mem::MaybeUninit, num::NonZeroIsize, ptr};
+use std::{cell::{Cell, RefCell}, ffi::CStr, mem::MaybeUninit, num::NonZeroIsize, ptr, sync::Once};
 
 #[cfg(target_os = "windows")]
 use windows_sys::Win32::{
-    Foundation::{HWND, HINSTANCE, LPARAM, LRESULT, RECT, WPARAM},
-    System::LibraryLoader::GetModuleHandleA,
+    Devices::HumanInterfaceDevice::MOUSE_MOVE_ABSOLUTE,
+    Foundation::{GetLastError, BOOL, ERROR_CLASS_ALREADY_EXISTS, HWND, HINSTANCE, LPARAM, LRESULT, POINT, RECT, WPARAM},
+    Graphics::{
+        Dwm::{DwmEnableBlurBehindWindow, DWM_BB_BLURREGION, DWM_BB_ENABLE, DWM_BLURBEHIND},
+        Gdi::{
+            ChangeDisplaySettingsExW, ClientToScreen, CreateDCW, CreateRectRgn, DeleteDC, DeleteObject, EnumDisplayMonitors,
+            EnumDisplaySettingsW, GetDeviceCaps, GetMonitorInfoW, HDC, HORZSIZE, VERTSIZE,
+            GetUpdateRect, ValidateRect, MonitorFromPoint, MonitorFromWindow, ScreenToClient, CDS_FULLSCREEN, DEVMODEW,
+            DISP_CHANGE_SUCCESSFUL, DM_BITSPERPEL, DM_DISPLAYFREQUENCY, DM_PELSHEIGHT, DM_PELSWIDTH, ENUM_CURRENT_SETTINGS,
+            HMONITOR, MONITORINFO, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTOPRIMARY,
+        },
+    },
+    System::LibraryLoader::{GetModuleHandleA, GetProcAddress},
+    UI::Input::KeyboardAndMouse::{
+        GetDoubleClickTime, GetFocus, GetKeyState, MapVirtualKeyW, TrackMouseEvent, TME_LEAVE, TRACKMOUSEEVENT,
+        MAPVK_VSC_TO_VK_EX, VIRTUAL_KEY,
+        VK_CAPITAL, VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LWIN, VK_MENU, VK_NUMLOCK, VK_RCONTROL, VK_RMENU, VK_RWIN,
+        VK_SHIFT,
+    },
+    UI::Controls::WM_MOUSELEAVE,
+    UI::Input::{
+        GetRawInputData, RegisterRawInputDevices, HRAWINPUT, RAWINPUT, RAWINPUTDEVICE, RAWINPUTHEADER, RIDEV_REMOVE,
+        RID_INPUT, RIM_TYPEMOUSE,
+    },
+    UI::HiDpi::{
+        GetDpiForMonitor, DPI_AWARENESS_CONTEXT, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, MDT_EFFECTIVE_DPI,
+    },
     UI::WindowsAndMessaging::{
-        AdjustWindowRectEx, LoadCursorW, LoadIconW, MessageBoxA, ShowWindow, CreateWindowExW, DestroyWindow, 
+        AdjustWindowRectEx, ClipCursor, PostMessageW, GetCursorPos, GetWindowRect, LoadCursorW, LoadIconW, SetForegroundWindow, SetWindowPos,
+        ShowWindow, CreateWindowExW, DestroyWindow, 
         DefWindowProcW, PeekMessageW, TranslateMessage, DispatchMessageW, GetClientRect,
-        RegisterClassW, WNDCLASSW, MSG,
-        CS_DBLCLKS, IDC_ARROW, IDI_APPLICATION, MB_ICONEXCLAMATION, MB_OK, SW_SHOW, SW_SHOWNOACTIVATE, 
+        GetWindowLongW, GetWindowLongPtrW, SetWindowLongW, SetWindowLongPtrW, HWND_TOP, SWP_FRAMECHANGED,
+        WS_OVERLAPPEDWINDOW,
+        RegisterClassW, WNDCLASSW, MSG, MINMAXINFO,
+        CW_USEDEFAULT, GWL_EXSTYLE, GWL_STYLE, GWLP_USERDATA, IDC_ARROW, IDI_APPLICATION,
+        SW_MAXIMIZE, SW_SHOW, UNICODE_NOCHAR, WS_POPUP, WS_EX_TOPMOST, WM_CHAR, WM_GETMINMAXINFO, WM_UNICHAR, MONITORINFOF_PRIMARY, WM_DISPLAYCHANGE, WM_DPICHANGED,
         WS_CAPTION, WS_EX_APPWINDOW, WS_MAXIMIZEBOX, WS_MINIMIZEBOX, WS_OVERLAPPED, WS_SYSMENU, WS_THICKFRAME,
         WM_DESTROY, PM_REMOVE, WM_CLOSE, WM_ERASEBKGND, WM_EXITSIZEMOVE, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN,
-        WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP,
-        WM_SYSKEYDOWN, WM_SYSKEYUP, WM_USER
+        WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_MOUSEHWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP, WHEEL_DELTA,
+        WM_XBUTTONDOWN, WM_XBUTTONUP, XBUTTON1, XBUTTON2, GetSystemMetrics, SM_CXDOUBLECLK, SM_CYDOUBLECLK, WM_INPUT,
+        HCURSOR, HTCLIENT, IDC_CROSS, IDC_HAND, IDC_IBEAM, IDC_NO, IDC_SIZEALL, IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE,
+        IDC_SIZEWE, IDC_WAIT, SetCursor, WM_SETCURSOR,
+        WM_KILLFOCUS, WM_MOVE, WM_SETFOCUS, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_USER, SWP_NOACTIVATE, SWP_NOSIZE,
+        SetWindowTextW, SIZE_MINIMIZED, SW_HIDE, SW_MINIMIZE, SW_RESTORE, SWP_NOMOVE, WM_PAINT, SWP_NOZORDER, WM_ENTERSIZEMOVE, WM_SIZE, WM_SIZING, WMSZ_BOTTOM, WMSZ_BOTTOMLEFT, WMSZ_LEFT, WMSZ_TOP, WMSZ_TOPLEFT,
+        WMSZ_TOPRIGHT,
     },
 };
 
 pub enum WindowEvent {
     Close,
-    Resize(u32, u32),
+    /// The client area of the window changed size. On Windows, `interactive` is set while the user is still dragging
+    /// the window border, and is followed by an event with `interactive` unset once the user lets go.
+    ///
+    /// On X11 `interactive` is best-effort only, as X11 does not announce interactive resizes. It is set for the
+    /// resizes that happen while a mouse button is held, so a resize by the application while the user holds a
+    /// button counts as interactive, and a window manager resize driven by the keyboard does not. Do not rely on it
+    /// for more than deferring expensive work, such as reallocating buffers, until the final event.
+    Resize { size: PhysicalSize, interactive: bool },
+    /// The window moved. Holds the new outer position, see [`Window::outer_position`].
+    Moved(i32, i32),
+    /// The window gained (`true`) or lost (`false`) keyboard focus.
+    Focused(bool),
+    /// The window needs to be redrawn, because [`Window::request_redraw`] was called or because the contents of part of
+    /// the window were lost. Sent at most once per [`Window::poll_messages`] call; see [`Window::damage`] for the
+    /// lost parts.
+    RedrawRequested,
+    /// A monitor was connected or disconnected, or the configuration of one changed, such as its video mode or
+    /// rotation. Sent at most once per [`Window::poll_messages`] call; query the monitors again with
+    /// [`Window::available_monitors`].
+    MonitorsChanged,
+    /// The scale factor of the window changed, because the user changed the setting or moved the window to another
+    /// monitor. `suggested_size` keeps the logical size of the client area; Windows has already resized the window to
+    /// it, on X11 pass it to [`Window::set_inner_size`] to do the same.
+    ScaleFactorChanged { scale: f64, suggested_size: PhysicalSize },
     Input(WindowInputEvent),
 }
 
 pub enum WindowInputEvent {
-    KeyDown(Keys),
-    KeyUp(Keys),
-    MouseDown(MouseButton),
-    MouseUp(MouseButton),
-    MouseMove(i16, i16),
-    MouseWheelMove(i16),
+    /// A key was pressed. `repeat` is set for the presses generated by holding the key down. `key` is `None` for the
+    /// keys [`Keys`] has no value for, which the `scancode` still identifies.
+    KeyDown { key: Option<Keys>, scancode: ScanCode, modifiers: Modifiers, repeat: bool },
+    KeyUp { key: Option<Keys>, scancode: ScanCode, modifiers: Modifiers },
+    /// A mouse button was pressed. `click_count` is 1 for a single click, 2 for a double click and so on; presses of
+    /// the same button count as one multi-click while they follow each other within the double click time and
+    /// distance the desktop is set to.
+    MouseDown { button: MouseButton, modifiers: Modifiers, click_count: u32 },
+    MouseUp { button: MouseButton, modifiers: Modifiers },
+    MouseMove { x: i16, y: i16, modifiers: Modifiers },
+    /// The cursor moved into the window.
+    CursorEntered,
+    /// The cursor left the window.
+    CursorLeft,
+    /// The mouse wheel or touchpad scrolled by `delta_x` and `delta_y` in `unit`. Positive `delta_y` scrolls up, away
+    /// from the user, and positive `delta_x` scrolls right. Touchpads and high resolution wheels report fractions of
+    /// a line.
+    MouseWheel { delta_x: f64, delta_y: f64, unit: ScrollUnit },
+    /// The mouse moved by (`dx`, `dy`) counts of its sensor, before pointer acceleration and unaffected by the edges
+    /// of the window and screen. Only sent while the window has focus, after [`Window::set_raw_input`] enabled it.
+    RawMouseMotion { dx: f64, dy: f64 },
+    /// Text typed by the user, after applying the keyboard layout, modifiers and compose sequences. Control
+    /// characters are not reported; use the key events for those.
+    Text(String),
+}
+
+/// How [`Window::set_cursor_grab`] holds the cursor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CursorGrabMode {
+    /// The cursor moves freely.
+    #[default]
+    None,
+    /// The cursor cannot leave the client area of the window.
+    Confined,
+    /// The cursor stays where it is. Read the motion of the mouse from [`WindowInputEvent::RawMouseMotion`].
+    Locked,
+}
+
+/// The cursor shown over the client area of the window, see [`Window::set_cursor_icon`]. On X11 the cursors come from
+/// the Xcursor theme of the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CursorIcon {
+    /// The usual arrow.
+    #[default]
+    Arrow,
+    /// The I-beam over editable or selectable text.
+    Text,
+    /// The pointing hand over links.
+    Hand,
+    Crosshair,
+    /// Something can be moved in any direction.
+    Move,
+    /// Something can be resized horizontally.
+    EwResize,
+    /// Something can be resized vertically.
+    NsResize,
+    /// Something can be resized along the diagonal from the top right to the bottom left.
+    NeswResize,
+    /// Something can be resized along the diagonal from the top left to the bottom right.
+    NwseResize,
+    /// The application is busy.
+    Wait,
+    /// The action under the cursor is not allowed.
+    NotAllowed,
+}
+
+impl CursorIcon {
+    /// Returns the names of the cursor in Xcursor themes, the name from the CSS cursor specification followed by
+    /// the name of the X core cursor older themes use.
+    #[cfg(target_os = "linux")]
+    fn xcursor_names(self) -> [&'static CStr; 2] {
+        match self {
+            Self::Arrow => [c"default", c"left_ptr"],
+            Self::Text => [c"text", c"xterm"],
+            Self::Hand => [c"pointer", c"hand2"],
+            Self::Crosshair => [c"crosshair", c"crosshair"],
+            Self::Move => [c"move", c"fleur"],
+            Self::EwResize => [c"ew-resize", c"sb_h_double_arrow"],
+            Self::NsResize => [c"ns-resize", c"sb_v_double_arrow"],
+            Self::NeswResize => [c"nesw-resize", c"fd_double_arrow"],
+            Self::NwseResize => [c"nwse-resize", c"bd_double_arrow"],
+            Self::Wait => [c"wait", c"watch"],
+            Self::NotAllowed => [c"not-allowed", c"crossed_circle"],
+        }
+    }
+
+    /// Returns the glyph of the cursor in the X core cursor font, as numbered in `X11/cursorfont.h`. The glyph after
+    /// it is its mask.
+    #[cfg(target_os = "linux")]
+    fn cursor_font_glyph(self) -> u16 {
+        match self {
+            Self::Arrow => 68,
+            Self::Text => 152,
+            Self::Hand => 60,
+            Self::Crosshair => 34,
+            Self::Move => 52,
+            Self::EwResize => 108,
+            Self::NsResize => 116,
+            // The font has no diagonal arrows, the corners point the same way.
+            Self::NeswResize => 12,
+            Self::NwseResize => 14,
+            Self::Wait => 150,
+            Self::NotAllowed => 0,
+        }
+    }
+
+    /// Returns the identifier of the system cursor to pass to LoadCursorW.
+    #[cfg(target_os = "windows")]
+    fn win32_id(self) -> windows_sys::core::PCWSTR {
+        match self {
+            Self::Arrow => IDC_ARROW,
+            Self::Text => IDC_IBEAM,
+            Self::Hand => IDC_HAND,
+            Self::Crosshair => IDC_CROSS,
+            Self::Move => IDC_SIZEALL,
+            Self::EwResize => IDC_SIZEWE,
+            Self::NsResize => IDC_SIZENS,
+            Self::NeswResize => IDC_SIZENESW,
+            Self::NwseResize => IDC_SIZENWSE,
+            Self::Wait => IDC_WAIT,
+            Self::NotAllowed => IDC_NO,
+        }
+    }
+}
+
+/// The unit of the deltas in [`WindowInputEvent::MouseWheel`]. Non-exhaustive, so that a unit such as pixels can be
+/// added for platforms that report the exact distance to scroll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScrollUnit {
+    /// Lines of text, or notches of a mouse wheel. Both the X11 scroll valuators and buttons and the Windows wheel
+    /// messages report lines.
+    Lines,
+}
+
+bitflags::bitflags! {
+    /// The modifier keys held, and the lock keys active, during an event.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Modifiers: u8 {
+        const SHIFT = 1 << 0;
+        const CONTROL = 1 << 1;
+        const ALT = 1 << 2;
+        const SUPER = 1 << 3;
+        const CAPS_LOCK = 1 << 4;
+        const NUM_LOCK = 1 << 5;
+    }
+}
+
+/// The physical position of a key on the keyboard, independent of the keyboard layout.
+///
+/// The value is the Linux evdev key code (`KEY_*` in `linux/input-event-codes.h`), so `ScanCode(17)` is the key
+/// labelled W on a QWERTY keyboard, Z on AZERTY and comma on Dvorak. Use [`Window::key_for_scancode`] to find the
+/// key the current layout puts there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScanCode(pub u32);
+
+/// A rectangle in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Returns whether `other` lies entirely within the rectangle.
+    pub fn contains(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x as i64 + other.width as i64 <= self.x as i64 + self.width as i64
+            && other.y as i64 + other.height as i64 <= self.y as i64 + self.height as i64
+    }
+}
+
+/// A size in physical pixels, the pixels of the monitor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PhysicalSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PhysicalSize {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// Converts the size to logical pixels at `scale_factor`.
+    pub fn to_logical(self, scale_factor: f64) -> LogicalSize {
+        LogicalSize::new(self.width as f64 / scale_factor, self.height as f64 / scale_factor)
+    }
+}
+
+/// A size in logical pixels, which are physical pixels divided by the scale factor. Content laid out in logical pixels
+/// keeps its apparent size on monitors of any pixel density.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LogicalSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LogicalSize {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+
+    /// Converts the size to physical pixels at `scale_factor`, rounding to the nearest pixel.
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalSize {
+        PhysicalSize::new((self.width * scale_factor).round() as u32, (self.height * scale_factor).round() as u32)
+    }
+}
+
+/// A monitor connected to the desktop, as it was when the handle was queried.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    name: String,
+    position: (i32, i32),
+    size: (u32, u32),
+    size_mm: (u32, u32),
+    refresh_rate_millihertz: Option<u32>,
+    scale_factor: f64,
+    primary: bool,
+    video_modes: Vec<VideoMode>,
+
+    #[cfg(target_os = "windows")]
+    handle: HMONITOR,
+}
+
+impl Monitor {
+    /// Returns the name of the monitor, such as `DP-1` on X11 and `\\.\DISPLAY1` on Windows.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the position of the top left corner of the monitor on the desktop.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// Returns the size of the monitor in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the physical size of the monitor in millimetres, or zeros if the monitor does not report it.
+    pub fn size_mm(&self) -> (u32, u32) {
+        self.size_mm
+    }
+
+    /// Returns the refresh rate of the current video mode of the monitor in millihertz, or `None` if it is unknown.
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.refresh_rate_millihertz
+    }
+
+    /// Returns the factor by which content on the monitor should be scaled, such as 2.0 on a monitor the system draws
+    /// everything twice as large on. X11 has no such setting per monitor, so this is the scale factor of the desktop,
+    /// or an estimate from the pixel density if the desktop has none.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Returns whether the monitor is the primary monitor.
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /// Returns the video modes the monitor supports.
+    pub fn video_modes(&self) -> &[VideoMode] {
+        &self.video_modes
+    }
+}
+
+/// A resolution, bit depth and refresh rate a monitor supports.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    size: (u32, u32),
+    bit_depth: u16,
+    refresh_rate_millihertz: u32,
+
+    #[cfg(target_os = "windows")]
+    device_name: [u16; 32],
+
+    #[cfg(target_os = "linux")]
+    mode: randr::Mode,
+    #[cfg(target_os = "linux")]
+    crtc: randr::Crtc,
+}
+
+impl VideoMode {
+    /// Returns the resolution of the video mode in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the number of bits per pixel of the video mode.
+    pub fn bit_depth(&self) -> u16 {
+        self.bit_depth
+    }
+
+    /// Returns the refresh rate of the video mode in millihertz, so 59950 for a 59.95 Hz mode.
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate_millihertz
+    }
+}
+
+/// A fullscreen mode of a window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fullscreen {
+    /// A borderless window covering the monitor, or the monitor the window is on with `None`. The video mode of the
+    /// monitor stays the same.
+    Borderless(Option<Monitor>),
+    /// A borderless window covering the monitor of the video mode, which is switched to the video mode until the
+    /// window leaves fullscreen, is dropped or the process exits. On X11 the video mode stays switched if the process
+    /// aborts, as on a panic with `panic = "abort"`, which skips the exit handler that restores it.
+    Exclusive(VideoMode),
+}
+
+/// An error returned by the fallible window functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowError {
+    /// The connection to the display server could not be established, or was lost.
+    Connection(String),
+    /// The display server rejected a request.
+    Protocol(String),
+    /// The requested position or size cannot be represented by the platform.
+    InvalidGeometry {
+        /// The requested x coordinate of the window, 0 if no position was requested.
+        x: i32,
+        /// The requested y coordinate of the window, 0 if no position was requested.
+        y: i32,
+        /// The requested width of the client area, clamped to `i32::MAX`.
+        width: i32,
+        /// The requested height of the client area, clamped to `i32::MAX`.
+        height: i32,
+    },
+    /// An atom required by the window is not known to the X server.
+    MissingAtom(&'static str),
+    /// A platform call failed with the given OS error code.
+    Os(u32),
+    /// The display server lacks the named extension or feature the call needs.
+    Unsupported(&'static str),
+}
+
+impl std::fmt::Display for WindowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connection(e) => write!(f, "Display server connection failed: {}", e),
+            Self::Protocol(e) => write!(f, "Display server protocol error: {}", e),
+            Self::InvalidGeometry { x, y, width, height } => write!(
+                f, "Invalid window geometry: position ({}, {}), size {}x{}", x, y, width, height
+            ),
+            Self::MissingAtom(name) => write!(f, "Missing X atom: {}", name),
+            Self::Os(code) => write!(f, "OS error: {}", code),
+            Self::Unsupported(feature) => write!(f, "Not supported by the display server: {}", feature),
+        }
+    }
+}
+
+impl std::error::Error for WindowError {}
+
+#[cfg(target_os = "linux")]
+impl From<xcb::ConnError> for WindowError {
+    fn from(e: xcb::ConnError) -> Self {
+        Self::Connection(e.to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<xcb::ProtocolError> for WindowError {
+    fn from(e: xcb::ProtocolError) -> Self {
+        Self::Protocol(e.to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<xcb::Error> for WindowError {
+    fn from(e: xcb::Error) -> Self {
+        match e {
+            xcb::Error::Connection(e) => e.into(),
+            xcb::Error::Protocol(e) => e.into(),
+        }
+    }
 }
 
 /// A cross-platform window wrapper.
 /// 
 /// # Examples
-/// ```
+/// ```no_run
 /// use simple_window::{Window, WindowEvent, WindowInputEvent};
 /// 
 /// fn main() {
@@ -63,14 +502,25 @@ pub enum WindowInputEvent {
 ///         window.poll_messages(|event| {
 ///             match event {
 ///                 WindowEvent::Close => is_running = false,
-///                 WindowEvent::Resize(width, height) => println!("Window resized: {}, {}", width, height),
+///                 WindowEvent::Resize { size, .. } => println!("Window resized: {}, {}", size.width, size.height),
+///                 WindowEvent::Moved(x, y) => println!("Window moved: {}, {}", x, y),
+///                 WindowEvent::RedrawRequested => println!("Redraw requested."),
+///                 WindowEvent::Focused(focused) => println!("Window {}.", if focused { "focused" } else { "unfocused" }),
+///                 WindowEvent::MonitorsChanged => println!("Monitors changed."),
+///                 WindowEvent::ScaleFactorChanged { scale, .. } => println!("Scale factor changed: {}", scale),
 ///                 WindowEvent::Input(event) => match event {
-///                     WindowInputEvent::MouseMove(x, y) => println!("Mouse moved!: {}, {}", x, y),
-///                     WindowInputEvent::KeyDown(key) => println!("Key pressed: {}", key.as_str()),
-///                     WindowInputEvent::KeyUp(key) => println!("Key released: {}", key.as_str()),
-///                     WindowInputEvent::MouseWheelMove(dz) => println!("Mouse wheel {}", if dz > 0 { "up" } else { "down" }),
-///                     WindowInputEvent::MouseDown(button) => println!("Mouse {} down.", button.as_str()),
-///                     WindowInputEvent::MouseUp(button) => println!("Mouse {} up.", button.as_str()),
+///                     WindowInputEvent::MouseMove { x, y, .. } => println!("Mouse moved!: {}, {}", x, y),
+///                     WindowInputEvent::KeyDown { key: Some(key), .. } => println!("Key pressed: {}", key.as_str()),
+///                     WindowInputEvent::KeyUp { key: Some(key), .. } => println!("Key released: {}", key.as_str()),
+///                     WindowInputEvent::KeyDown { scancode, .. } => println!("Key pressed: {:?}", scancode),
+///                     WindowInputEvent::KeyUp { scancode, .. } => println!("Key released: {:?}", scancode),
+///                     WindowInputEvent::MouseWheel { delta_x, delta_y, .. } => println!("Mouse wheel: {}, {}", delta_x, delta_y),
+///                     WindowInputEvent::RawMouseMotion { dx, dy } => println!("Raw mouse motion: {}, {}", dx, dy),
+///                     WindowInputEvent::MouseDown { button, .. } => println!("Mouse {} down.", button.as_str()),
+///                     WindowInputEvent::MouseUp { button, .. } => println!("Mouse {} up.", button.as_str()),
+///                     WindowInputEvent::Text(text) => println!("Text input: {}", text),
+///                     WindowInputEvent::CursorEntered => println!("Cursor entered."),
+///                     WindowInputEvent::CursorLeft => println!("Cursor left."),
 ///                 },
 ///             }
 ///         });
@@ -78,582 +528,4196 @@ pub enum WindowInputEvent {
 /// }
 /// ```
 pub struct Window {
-    previous_size: (u32, u32),
+    redraw_requested: bool,
+    /// The lost parts of the window not yet reported by a [`WindowEvent::RedrawRequested`].
+    pending_damage: Vec<Rect>,
+    damage: Vec<Rect>,
+    fullscreen: Option<Fullscreen>,
+    click_counter: ClickCounter,
+    cursor_grab: CursorGrabMode,
+    cursor_visible: bool,
+    cursor_icon: CursorIcon,
 
     #[cfg(target_os = "windows")]
     h_instance: HINSTANCE,
     #[cfg(target_os = "windows")]
     hwnd: HWND,
+    #[cfg(target_os = "windows")]
+    state: Box<Win32WindowState>,
+    #[cfg(target_os = "windows")]
+    high_surrogate: Option<u16>,
+    #[cfg(target_os = "windows")]
+    tracking_mouse_leave: bool,
+    /// The style and rectangle of the window from before it became fullscreen.
+    #[cfg(target_os = "windows")]
+    windowed: Option<(u32, RECT)>,
+    /// The name of the display device switched to another video mode by exclusive fullscreen.
+    #[cfg(target_os = "windows")]
+    switched_display: Option<[u16; 32]>,
     
+    #[cfg(target_os = "linux")]
+    previous_position: (i32, i32),
+    #[cfg(target_os = "linux")]
+    previous_size: (u32, u32),
+    #[cfg(target_os = "linux")]
+    interactive_resize: bool,
     #[cfg(target_os = "linux")]
     connection: xcb::Connection,
     #[cfg(target_os = "linux")]
-    window: u32,
+    window: x::Window,
     #[cfg(target_os = "linux")]
     screen: c_int,
     #[cfg(target_os = "linux")]
     wm_del_window: x::Atom,
+    #[cfg(target_os = "linux")]
+    atoms: XAtoms,
+    #[cfg(target_os = "linux")]
+    input_context: Option<XInputContext>,
+    /// Loads the cursors of the Xcursor theme, `None` if libxcb-cursor is missing or failed to set up. The cursors
+    /// then come from the X core cursor font.
+    #[cfg(target_os = "linux")]
+    cursor_context: Option<XCursorContext>,
+    #[cfg(target_os = "linux")]
+    detectable_auto_repeat: bool,
+    #[cfg(target_os = "linux")]
+    pressed_keys: HashSet<x::Keycode>,
+    #[cfg(target_os = "linux")]
+    pending_event: Option<xcb::Event>,
+    /// The size constraints set by the user, which a window that is not resizable overrides.
+    #[cfg(target_os = "linux")]
+    size_hints: utility::WmSizeHints,
+    #[cfg(target_os = "linux")]
+    resizable: bool,
+    /// The RandR version the server supports, or zeros without RandR.
+    #[cfg(target_os = "linux")]
+    randr_version: (u32, u32),
+    /// The `_XSETTINGS_S<screen>` selection, owned by the XSETTINGS manager of the screen.
+    #[cfg(target_os = "linux")]
+    xsettings_selection: x::Atom,
+    #[cfg(target_os = "linux")]
+    xsettings_owner: x::Window,
+    /// The DPI the desktop is set to, if any.
+    #[cfg(target_os = "linux")]
+    xft_dpi: Option<f64>,
+    /// The longest time in milliseconds, and the largest distance in pixels, between the presses of a double click.
+    #[cfg(target_os = "linux")]
+    double_click: (u32, i32),
+    /// The configuration of the CRTC switched to another video mode by exclusive fullscreen.
+    #[cfg(target_os = "linux")]
+    saved_crtc: Option<SavedCrtc>,
+    /// Whether the server supports XInput 2.1, which the buttons and motion of the window are read from.
+    #[cfg(target_os = "linux")]
+    xinput2: bool,
+    #[cfg(target_os = "linux")]
+    focused: bool,
+    /// The position the cursor is held at while it is locked.
+    #[cfg(target_os = "linux")]
+    locked_cursor_position: Option<(i16, i16)>,
+    /// Whether the window grabbed the pointer to confine or lock the cursor.
+    #[cfg(target_os = "linux")]
+    pointer_grabbed: bool,
+    /// The scroll valuators of the devices that moved the cursor inside the window, by device id.
+    #[cfg(target_os = "linux")]
+    scroll_valuators: HashMap<u16, Vec<ScrollValuator>>,
+    /// The cursor position of the last `MouseMove` read from an XInput 2 event.
+    #[cfg(target_os = "linux")]
+    cursor_position: Option<(i16, i16)>,
+    /// The width of the left and top window manager decorations, updated as `_NET_FRAME_EXTENTS` changes.
+    #[cfg(target_os = "linux")]
+    frame_extents: (i32, i32),
+    /// Whether the window is shown, as set by [`Window::set_visible`]. Minimizing does not change it.
+    #[cfg(target_os = "linux")]
+    visible: bool,
+    /// Whether a window manager put the window into a frame of its own.
+    #[cfg(target_os = "linux")]
+    reparented: bool,
 }
 
-#[cfg(target_os = "windows")]
-const CUSTOM_CLOSE_MESSAGE: u32 = WM_USER + 0;
-#[cfg(target_os = "windows")]
-const CUSTOM_SIZE_MESSAGE: u32 = WM_USER + 1;
+/// The atoms of the window manager hints and protocols the X11 backend uses, interned at window creation.
+#[cfg(target_os = "linux")]
+struct XAtoms {
+    wm_change_state: x::Atom,
+    motif_wm_hints: x::Atom,
+    utf8_string: x::Atom,
+    net_wm_name: x::Atom,
+    net_wm_state: x::Atom,
+    net_wm_state_maximized_vert: x::Atom,
+    net_wm_state_maximized_horz: x::Atom,
+    net_wm_state_fullscreen: x::Atom,
+    net_wm_state_above: x::Atom,
+    net_active_window: x::Atom,
+    net_frame_extents: x::Atom,
+    manager: x::Atom,
+    xsettings_settings: x::Atom,
+}
 
-#[cfg(target_os = "windows")]
-extern "system" fn win32_process_message(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
-    use windows_sys::Win32::{Foundation::GetLastError, UI::WindowsAndMessaging::{PostMessageW, PostQuitMessage}};
+#[cfg(target_os = "linux")]
+impl XAtoms {
+    fn intern(conn: &xcb::Connection) -> Result<Self, WindowError> {
+        let [
+            wm_change_state, motif_wm_hints, utf8_string, net_wm_name, net_wm_state, net_wm_state_maximized_vert,
+            net_wm_state_maximized_horz, net_wm_state_fullscreen, net_wm_state_above, net_active_window,
+            net_frame_extents, manager, xsettings_settings,
+        ] = Window::intern_atoms(conn, [
+            "WM_CHANGE_STATE", "_MOTIF_WM_HINTS", "UTF8_STRING", "_NET_WM_NAME", "_NET_WM_STATE",
+            "_NET_WM_STATE_MAXIMIZED_VERT", "_NET_WM_STATE_MAXIMIZED_HORZ", "_NET_WM_STATE_FULLSCREEN",
+            "_NET_WM_STATE_ABOVE", "_NET_ACTIVE_WINDOW", "_NET_FRAME_EXTENTS", "MANAGER", "_XSETTINGS_SETTINGS",
+        ])?;
+
+        Ok(Self {
+            wm_change_state,
+            motif_wm_hints,
+            utf8_string,
+            net_wm_name,
+            net_wm_state,
+            net_wm_state_maximized_vert,
+            net_wm_state_maximized_horz,
+            net_wm_state_fullscreen,
+            net_wm_state_above,
+            net_active_window,
+            net_frame_extents,
+            manager,
+            xsettings_settings,
+        })
+    }
+}
 
-    match msg {
-        WM_ERASEBKGND => 1,
-        WM_CLOSE => {
-            unsafe { PostMessageW(hwnd, CUSTOM_CLOSE_MESSAGE, 0, 0); }
-            0
-        }
-        WM_DESTROY => {
-            unsafe { PostQuitMessage(0); }
-            0
-        },
-        WM_EXITSIZEMOVE => {
-            // println!("A");
-            if unsafe { PostMessageW(hwnd, CUSTOM_SIZE_MESSAGE, 0, 0) } == 0 {
-                println!("Failed to post. {}", unsafe { GetLastError() });
-            }
+/// A valuator of an XInput 2 device that reports scrolling.
+#[cfg(target_os = "linux")]
+struct ScrollValuator {
+    number: u16,
+    horizontal: bool,
+    /// The change of the value that scrolls one line.
+    increment: f64,
+    /// The value in the last event, or the value when the valuator was queried. Unknown if the device did not report
+    /// it.
+    value: Option<f64>,
+}
 
-            unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) }
-        },
-        _ => unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) },
-    }
+/// The configuration of a CRTC from before exclusive fullscreen switched its video mode.
+#[cfg(target_os = "linux")]
+#[derive(Clone)]
+struct SavedCrtc {
+    root: x::Window,
+    crtc: randr::Crtc,
+    mode: randr::Mode,
+    x: i16,
+    y: i16,
+    rotation: randr::Rotation,
+    outputs: Vec<randr::Output>,
+    randr_version: (u32, u32),
 }
 
-impl Window {
-    /// Creates a new window at position (`x`, `y`), and name `window_name`.
-    pub fn new(
-        window_name: &str,
-        x: i32, y: i32,
-        width: i32, height: i32,
-    ) -> Self {
-        #[cfg(target_os = "windows")]
-        { Self::new_win32(window_name, x, y, width, height) }
+/// The CRTCs to restore when the process exits, which skips the `Drop` of the windows that switched them. The X server
+/// keeps video modes after the client that set them disconnects. An abort runs no exit handlers, and leaves them
+/// switched.
+#[cfg(target_os = "linux")]
+static SAVED_CRTCS: Mutex<Vec<SavedCrtc>> = Mutex::new(Vec::new());
 
-        #[cfg(target_os = "linux")]
-        { Self::new_linux_x(window_name, x, y, width, height) }
-    }
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> c_int;
+}
 
-    /// Polls and parses system messages directed at the window and passes them on to the `event_closure` closure.
-    pub fn poll_messages(&mut self, event_closure: impl FnMut(WindowEvent)) {
-        #[cfg(target_os = "windows")]
-        { self.poll_messages_win32(event_closure); }
+#[cfg(target_os = "linux")]
+impl SavedCrtc {
+    fn register(saved_crtc: SavedCrtc) {
+        static REGISTER_EXIT_HANDLER: Once = Once::new();
 
-        #[cfg(target_os = "linux")]
-        { self.poll_messages_linux_x(event_closure); }
-    }
+        REGISTER_EXIT_HANDLER.call_once(|| unsafe {
+            atexit(Self::restore_at_exit);
+        });
 
-    pub fn raw_window_handle(&self) -> RawWindowHandle {
-        #[cfg(target_os = "windows")]
-        { self.raw_window_handle_win32() }
+        SAVED_CRTCS.lock().unwrap_or_else(PoisonError::into_inner).push(saved_crtc);
+    }
 
-        #[cfg(target_os = "linux")]
-        { self.raw_window_handle_linux_x() }
+    fn unregister(crtc: randr::Crtc) {
+        SAVED_CRTCS.lock().unwrap_or_else(PoisonError::into_inner).retain(|saved_crtc| saved_crtc.crtc != crtc);
     }
 
-    pub fn raw_display_handle(&self) -> RawDisplayHandle {
-        #[cfg(target_os = "windows")]
-        { self.raw_display_handle_windows() }
+    /// Restores the saved CRTCs. Must not panic, as it runs during the teardown of the process.
+    extern "C" fn restore_at_exit() {
+        // A thread that exited the process while holding the lock would never release it.
+        let saved_crtcs = match SAVED_CRTCS.try_lock() {
+            Ok(mut saved_crtcs) => std::mem::take(&mut *saved_crtcs),
+            Err(TryLockError::Poisoned(e)) => std::mem::take(&mut *e.into_inner()),
+            Err(TryLockError::WouldBlock) => {
+                log::warn!("Failed to restore the video modes: the saved video modes are locked.");
+                return;
+            },
+        };
 
-        #[cfg(target_os = "linux")]
-        { self.raw_display_handle_linux_x() }
+        if saved_crtcs.is_empty() {
+            return;
+        }
+
+        // The connections of the windows may be gone, or in use by another thread.
+        let conn = match xcb::Connection::connect_with_extensions(None, &[xcb::Extension::RandR], &[]) {
+            Ok((conn, _)) => conn,
+            Err(e) => {
+                log::warn!("Failed to restore the video modes: {}", e);
+                return;
+            },
+        };
+
+        for saved_crtc in saved_crtcs {
+            if let Err(e) = saved_crtc.restore(&conn) {
+                log::warn!("Failed to restore the video mode: {}", e);
+            }
+        }
+    }
+
+    fn restore(&self, conn: &xcb::Connection) -> Result<(), WindowError> {
+        let resources = ScreenResources::query(conn, self.root, self.randr_version)?;
+
+        let reply = conn.wait_for_reply(conn.send_request(&randr::SetCrtcConfig {
+            crtc: self.crtc,
+            timestamp: x::CURRENT_TIME,
+            config_timestamp: resources.config_timestamp,
+            x: self.x,
+            y: self.y,
+            mode: self.mode,
+            rotation: self.rotation,
+            outputs: &self.outputs,
+        }))?;
+
+        Window::check_set_config(reply.status())
     }
 }
 
+/// The RandR screen resources used to enumerate the outputs and their video modes.
 #[cfg(target_os = "linux")]
-impl Window {
-    fn new_linux_x(
-        window_name: &str,
-        x: i32, y: i32,
-        width: i32, height: i32,
-    ) -> Self {
-        let (conn, screen_num) = xcb::Connection::connect_with_xlib_display().unwrap();
-
-        let setup = conn.get_setup();
-        let screen = setup.roots().nth(screen_num as usize).unwrap();
+struct ScreenResources {
+    config_timestamp: x::Timestamp,
+    outputs: Vec<randr::Output>,
+    modes: Vec<randr::ModeInfo>,
+}
 
-        let window: x::Window = conn.generate_id();
+#[cfg(target_os = "linux")]
+impl ScreenResources {
+    /// Queries the resources, which before RandR 1.3 makes the server poll the outputs for changes.
+    fn query(conn: &xcb::Connection, window: x::Window, randr_version: (u32, u32)) -> Result<Self, WindowError> {
+        if randr_version >= (1, 3) {
+            let reply = conn.wait_for_reply(conn.send_request(&randr::GetScreenResourcesCurrent { window }))?;
+
+            Ok(Self {
+                config_timestamp: reply.config_timestamp(),
+                outputs: reply.outputs().to_vec(),
+                modes: reply.modes().to_vec(),
+            })
+        } else {
+            let reply = conn.wait_for_reply(conn.send_request(&randr::GetScreenResources { window }))?;
+
+            Ok(Self {
+                config_timestamp: reply.config_timestamp(),
+                outputs: reply.outputs().to_vec(),
+                modes: reply.modes().to_vec(),
+            })
+        }
+    }
 
-        let cookie = conn.send_request_checked(&x::CreateWindow {
-            depth: x::COPY_FROM_PARENT as u8,
-            wid: window,
-            parent: screen.root(),
-            x: x.try_into().unwrap(),
-            y: y.try_into().unwrap(),
-            width: width.try_into().unwrap(),
-            height: height.try_into().unwrap(),
-            border_width: 0,
-            class: x::WindowClass::InputOutput,
-            visual: screen.root_visual(),
-            value_list: &[
-                x::Cw::BackPixel(screen.white_pixel()),
-                x::Cw::EventMask(x::EventMask::BUTTON_PRESS | x::EventMask::BUTTON_RELEASE | x::EventMask::KEY_PRESS
-                    | x::EventMask::KEY_RELEASE | x::EventMask::EXPOSURE | x::EventMask::POINTER_MOTION
-                    | x::EventMask::STRUCTURE_NOTIFY
-                ),
-            ],
-        });
-        conn.check_request(cookie).unwrap();
+    fn mode_info(&self, mode: randr::Mode) -> Option<&randr::ModeInfo> {
+        self.modes.iter().find(|mode_info| mode_info.id == mode.resource_id())
+    }
+}
 
-        let cookie = conn.send_request_checked(&x::ChangeProperty {
-            mode: x::PropMode::Replace,
-            window,
-            property: x::ATOM_WM_NAME,
-            r#type: x::ATOM_STRING,
-            data: window_name.as_bytes(),
-        });
-        conn.check_request(cookie).unwrap();
+/// An Xlib input method and input context, used to turn key presses into text.
+#[cfg(target_os = "linux")]
+struct XInputContext {
+    im: x11::xlib::XIM,
+    ic: x11::xlib::XIC,
+}
 
-        conn.send_request(&x::MapWindow {
-            window,
-        });
+// The input context is only ever used through the `Window` owning it.
+#[cfg(target_os = "linux")]
+unsafe impl Send for XInputContext {}
 
-        // Get atoms.
-        let (wm_protocols, wm_del_window) = {
-            let cookies = (
-                conn.send_request(&x::InternAtom {
-                    only_if_exists: true,
-                    name: b"WM_PROTOCOLS",
-                }),
-                conn.send_request(&x::InternAtom {
-                    only_if_exists: true,
-                    name: b"WM_DELETE_WINDOW",
-                }),
-            );
+#[cfg(target_os = "linux")]
+impl XInputContext {
+    /// Opens the input method built into Xlib, which handles the compose and dead key sequences of the Compose file
+    /// of the current locale, and creates an input context for `window`.
+    ///
+    /// Input method servers such as IBus and Fcitx, configured by `XMODIFIERS`, are not used: they talk to Xlib
+    /// through events, and the event queue belongs to xcb.
+    fn new(display: *mut x11::xlib::Display, window: x::Window) -> Option<Self> {
+        use x11::xlib::{XCloseIM, XCreateIC, XOpenIM, XSetLocaleModifiers, XIMPreeditNothing, XIMStatusNothing,
+            XNClientWindow_0, XNFocusWindow_0, XNInputStyle_0};
+
+        unsafe { XSetLocaleModifiers(c"@im=none".as_ptr()); }
+
+        let im = unsafe { XOpenIM(display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut()) };
+
+        if im.is_null() {
+            return None;
+        }
 
-            (
-                conn.wait_for_reply(cookies.0).unwrap().atom(),
-                conn.wait_for_reply(cookies.1).unwrap().atom(),
+        let ic = unsafe {
+            XCreateIC(
+                im,
+                XNInputStyle_0.as_ptr(), (XIMPreeditNothing | XIMStatusNothing) as c_long,
+                XNClientWindow_0.as_ptr(), window.resource_id() as c_ulong,
+                XNFocusWindow_0.as_ptr(), window.resource_id() as c_ulong,
+                ptr::null_mut::<c_void>(),
             )
         };
 
-        conn.check_request(conn.send_request_checked(&x::ChangeProperty {
-            mode: x::PropMode::Replace,
+        if ic.is_null() {
+            unsafe { XCloseIM(im); }
+            return None;
+        }
+
+        Some(Self { im, ic })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for XInputContext {
+    fn drop(&mut self) {
+        unsafe {
+            x11::xlib::XDestroyIC(self.ic);
+            x11::xlib::XCloseIM(self.im);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[link(name = "dl")]
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+#[cfg(target_os = "linux")]
+const RTLD_LAZY: c_int = 1;
+
+#[cfg(target_os = "linux")]
+type XcbCursorContextNew = unsafe extern "C" fn(*mut xcb::ffi::xcb_connection_t, *const u8, *mut *mut c_void) -> c_int;
+#[cfg(target_os = "linux")]
+type XcbCursorLoadCursor = unsafe extern "C" fn(*mut c_void, *const c_char) -> u32;
+#[cfg(target_os = "linux")]
+type XcbCursorContextFree = unsafe extern "C" fn(*mut c_void);
+
+/// The functions of libxcb-cursor. The library is loaded at run time, as not every system has it installed.
+#[cfg(target_os = "linux")]
+struct XCursorLibrary {
+    context_new: XcbCursorContextNew,
+    load_cursor: XcbCursorLoadCursor,
+    context_free: XcbCursorContextFree,
+}
+
+#[cfg(target_os = "linux")]
+impl XCursorLibrary {
+    /// Returns the library, loading it on first use, or `None` if it is missing. It stays loaded for the rest of the
+    /// process.
+    fn get() -> Option<&'static Self> {
+        static LIBRARY: OnceLock<Option<XCursorLibrary>> = OnceLock::new();
+
+        LIBRARY.get_or_init(|| unsafe {
+            let handle = dlopen(c"libxcb-cursor.so.0".as_ptr(), RTLD_LAZY);
+
+            if handle.is_null() {
+                return None;
+            }
+
+            let symbol = |name: &CStr| NonNull::new(dlsym(handle, name.as_ptr()));
+
+            Some(Self {
+                context_new: std::mem::transmute::<NonNull<c_void>, XcbCursorContextNew>(
+                    symbol(c"xcb_cursor_context_new")?
+                ),
+                load_cursor: std::mem::transmute::<NonNull<c_void>, XcbCursorLoadCursor>(
+                    symbol(c"xcb_cursor_load_cursor")?
+                ),
+                context_free: std::mem::transmute::<NonNull<c_void>, XcbCursorContextFree>(
+                    symbol(c"xcb_cursor_context_free")?
+                ),
+            })
+        }).as_ref()
+    }
+}
+
+/// A libxcb-cursor context, which finds the cursors of the Xcursor theme set by the `XCURSOR_THEME` environment
+/// variable or the `Xcursor.theme` resource.
+#[cfg(target_os = "linux")]
+struct XCursorContext {
+    library: &'static XCursorLibrary,
+    ctx: NonNull<c_void>,
+}
+
+// The context is only ever used through the `Window` owning it.
+#[cfg(target_os = "linux")]
+unsafe impl Send for XCursorContext {}
+
+#[cfg(target_os = "linux")]
+impl XCursorContext {
+    fn new(conn: &xcb::Connection, screen_num: c_int) -> Option<Self> {
+        let library = XCursorLibrary::get()?;
+        let screen = conn.get_setup().roots().nth(screen_num as usize)?;
+        let mut ctx = ptr::null_mut();
+
+        // The screen is passed in its wire format, which is what xcb_screen_t describes.
+        let result = unsafe {
+            (library.context_new)(conn.get_raw_conn(), screen as *const x::Screen as *const u8, &mut ctx)
+        };
+
+        if result < 0 {
+            return None;
+        }
+
+        NonNull::new(ctx).map(|ctx| Self { library, ctx })
+    }
+
+    /// Creates the cursor of the first of `names` the theme has, falling back to the X core cursors.
+    fn load(&self, names: &[&CStr]) -> Option<x::Cursor> {
+        names.iter()
+            .map(|name| unsafe { (self.library.load_cursor)(self.ctx.as_ptr(), name.as_ptr()) })
+            .find(|&cursor| cursor != 0)
+            .map(|cursor| unsafe { x::Cursor::new(cursor) })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for XCursorContext {
+    fn drop(&mut self) {
+        unsafe { (self.library.context_free)(self.ctx.as_ptr()); }
+    }
+}
+
+#[cfg(target_os = "windows")]
+const CUSTOM_CLOSE_MESSAGE: u32 = WM_USER + 0;
+#[cfg(target_os = "windows")]
+const CUSTOM_SIZE_MESSAGE: u32 = WM_USER + 1;
+#[cfg(target_os = "windows")]
+const CUSTOM_CHAR_MESSAGE: u32 = WM_USER + 2;
+#[cfg(target_os = "windows")]
+const CUSTOM_FOCUS_MESSAGE: u32 = WM_USER + 3;
+#[cfg(target_os = "windows")]
+const CUSTOM_MOVE_MESSAGE: u32 = WM_USER + 4;
+#[cfg(target_os = "windows")]
+const CUSTOM_DISPLAY_CHANGE_MESSAGE: u32 = WM_USER + 5;
+#[cfg(target_os = "windows")]
+const CUSTOM_SCALE_MESSAGE: u32 = WM_USER + 6;
+
+/// Window state the window procedure needs access to. Pointed to by the `GWLP_USERDATA` of the window.
+#[cfg(target_os = "windows")]
+#[derive(Default)]
+struct Win32WindowState {
+    min_inner_size: Cell<Option<(u32, u32)>>,
+    max_inner_size: Cell<Option<(u32, u32)>>,
+    resize_increments: Cell<Option<(u32, u32)>>,
+    aspect_ratio: Cell<Option<(u32, u32)>>,
+    /// The client area size last reported.
+    inner_size: Cell<(u32, u32)>,
+    /// Whether the user is moving or resizing the window, which Windows handles in a modal loop of its own.
+    in_size_move: Cell<bool>,
+    resized_in_size_move: Cell<bool>,
+    /// The closure of the running [`Window::poll_messages`] call, through which the window procedure reports the
+    /// messages handled inside DispatchMessageW. Taken while it runs.
+    event_closure: Cell<Option<*mut dyn FnMut(WindowEvent)>>,
+    /// The cursor shown over the client area, 0 while the cursor is hidden.
+    cursor: Cell<HCURSOR>,
+    /// The outer position last reported.
+    outer_position: Cell<(i32, i32)>,
+    /// The damage the modal loop collected since its last [`WindowEvent::RedrawRequested`].
+    damage: RefCell<Vec<Rect>>,
+    /// The damage the modal loop reported with a [`WindowEvent::RedrawRequested`] during the running
+    /// [`Window::poll_messages`] call, if it sent one.
+    reported_damage: Cell<Option<Vec<Rect>>>,
+    cursor_grab: Cell<CursorGrabMode>,
+    /// Whether the monitors changed during the running [`Window::poll_messages`] call, reported once at its end.
+    monitors_changed: Cell<bool>,
+}
+
+#[cfg(target_os = "windows")]
+impl Win32WindowState {
+    /// Returns the state of the window `hwnd`, or `None` while CreateWindowExW has not returned yet.
+    fn of<'a>(hwnd: HWND) -> Option<&'a Self> {
+        unsafe { (GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const Self).as_ref() }
+    }
+
+    /// Passes `event` to the closure of the running [`Window::poll_messages`] call. Returns `false` if there is no
+    /// such call, or if the closure is already running.
+    fn emit(&self, event: WindowEvent) -> bool {
+        let Some(closure) = self.event_closure.take() else { return false };
+
+        unsafe { (*closure)(event) };
+        self.event_closure.set(Some(closure));
+
+        true
+    }
+
+    /// Returns the event for a resize of the window `hwnd`, or `None` if the size did not change since the last
+    /// event. The first event after an interactive resize reports the size even if it did not change.
+    fn resize_event(&self, hwnd: HWND) -> Option<WindowEvent> {
+        let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+        unsafe { GetClientRect(hwnd, &mut rect); }
+
+        let size = ((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32);
+        let interactive = self.in_size_move.get();
+        let finished = !interactive && self.resized_in_size_move.replace(false);
+
+        if self.inner_size.replace(size) == size && !finished {
+            return None;
+        }
+
+        if interactive {
+            self.resized_in_size_move.set(true);
+        }
+
+        Some(WindowEvent::Resize { size: PhysicalSize::new(size.0, size.1), interactive })
+    }
+
+    /// Reports the damage the modal loop collected with a [`WindowEvent::RedrawRequested`], unless the running
+    /// [`Window::poll_messages`] call already sent one.
+    fn report_redraw(&self) {
+        let reported_damage = self.reported_damage.take();
+
+        if reported_damage.is_some() || self.event_closure.get().is_none() {
+            self.reported_damage.set(reported_damage);
+            return;
+        }
+
+        self.reported_damage.set(Some(self.damage.take()));
+        self.emit(WindowEvent::RedrawRequested);
+    }
+
+    /// Returns the event for a move of the window `hwnd`, or `None` if the position did not change since the last
+    /// event.
+    fn move_event(&self, hwnd: HWND) -> Option<WindowEvent> {
+        let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+
+        if unsafe { GetWindowRect(hwnd, &mut rect) } == 0 {
+            return None;
+        }
+
+        let position = (rect.left, rect.top);
+
+        (self.outer_position.replace(position) != position).then_some(WindowEvent::Moved(position.0, position.1))
+    }
+
+    /// Reports the custom message `msg` of the window `hwnd` to the running [`Window::poll_messages`] call, or else
+    /// posts it for the next one.
+    fn report(hwnd: HWND, msg: u32, w_param: WPARAM) {
+        match Self::of(hwnd) {
+            // The closure is missing outside of poll_messages, and while it runs.
+            Some(state) if state.event_closure.get().is_some() => state.handle_custom_message(hwnd, msg, w_param),
+            _ => unsafe { PostMessageW(hwnd, msg, w_param, 0); },
+        }
+    }
+
+    /// Reports the event of the custom message `msg` of the window `hwnd`. The window procedure handles the posted
+    /// custom messages, as the modal loop Windows runs while the user moves or resizes the window dispatches them
+    /// there instead of to the message loop of [`Window::poll_messages`].
+    fn handle_custom_message(&self, hwnd: HWND, msg: u32, w_param: WPARAM) {
+        let event = match msg {
+            CUSTOM_CLOSE_MESSAGE => Some(WindowEvent::Close),
+            CUSTOM_SIZE_MESSAGE => self.resize_event(hwnd),
+            CUSTOM_MOVE_MESSAGE => self.move_event(hwnd),
+            CUSTOM_DISPLAY_CHANGE_MESSAGE => {
+                self.monitors_changed.set(true);
+                None
+            },
+            // The window already has the size Windows suggested.
+            CUSTOM_SCALE_MESSAGE => Some(WindowEvent::ScaleFactorChanged {
+                scale: w_param as f64 / 96.0,
+                suggested_size: Window::client_size_win32(hwnd),
+            }),
+            CUSTOM_CHAR_MESSAGE => char::from_u32(w_param as u32)
+                .filter(|c| !c.is_control())
+                .map(|c| WindowEvent::Input(WindowInputEvent::Text(c.to_string()))),
+            CUSTOM_FOCUS_MESSAGE => {
+                let focused = w_param != 0;
+
+                // The clip of the cursor applies to the whole desktop, it is only held while the window has focus.
+                if self.cursor_grab.get() != CursorGrabMode::None {
+                    Window::clip_cursor_win32(hwnd, self.cursor_grab.get(), focused);
+                }
+
+                Some(WindowEvent::Focused(focused))
+            },
+            _ => None,
+        };
+
+        if let Some(event) = event {
+            self.emit(event);
+        }
+    }
+}
+
+/// Unsets the closure of the running [`Window::poll_messages`] call in the window state when dropped, so the window
+/// procedure cannot call it after the call returns, also if the closure panics.
+#[cfg(target_os = "windows")]
+struct EventClosureGuard(*const Win32WindowState);
+
+#[cfg(target_os = "windows")]
+impl Drop for EventClosureGuard {
+    fn drop(&mut self) {
+        unsafe { (*self.0).event_closure.set(None); }
+    }
+}
+
+#[cfg(target_os = "windows")]
+extern "system" fn win32_process_message(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    use windows_sys::Win32::UI::WindowsAndMessaging::PostQuitMessage;
+
+    match msg {
+        WM_GETMINMAXINFO => {
+            if let Some(state) = Win32WindowState::of(hwnd) {
+                let info = unsafe { &mut *(l_param as *mut MINMAXINFO) };
+
+                if let Some(size) = state.min_inner_size.get() {
+                    let (width, height) = Window::win32_outer_size(hwnd, size);
+                    info.ptMinTrackSize = POINT { x: width, y: height };
+                }
+
+                if let Some(size) = state.max_inner_size.get() {
+                    let (width, height) = Window::win32_outer_size(hwnd, size);
+                    info.ptMaxTrackSize = POINT { x: width, y: height };
+                }
+            }
+
+            0
+        },
+        WM_SIZING => {
+            if let Some(state) = Win32WindowState::of(hwnd) {
+                Window::win32_constrain_sizing(hwnd, state, w_param as u32, unsafe { &mut *(l_param as *mut RECT) });
+            }
+
+            1
+        },
+        WM_ERASEBKGND => 1,
+        WM_PAINT => {
+            // Only dispatched here by the modal loop Windows runs while the user moves or resizes the window.
+            let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+
+            unsafe {
+                GetUpdateRect(hwnd, &mut rect, 0);
+                ValidateRect(hwnd, ptr::null());
+            }
+
+            if let Some(state) = Win32WindowState::of(hwnd) {
+                Window::add_damage(&mut state.damage.borrow_mut(), Rect {
+                    x: rect.left,
+                    y: rect.top,
+                    width: (rect.right - rect.left) as u32,
+                    height: (rect.bottom - rect.top) as u32,
+                });
+
+                state.report_redraw();
+            }
+
+            0
+        },
+        WM_UNICHAR => {
+            // Returning 1 to the UNICODE_NOCHAR probe announces support for WM_UNICHAR, which carries full code
+            // points instead of UTF-16 units. A handled character returns 0.
+            if w_param as u32 == UNICODE_NOCHAR {
+                return 1;
+            }
+
+            Win32WindowState::report(hwnd, CUSTOM_CHAR_MESSAGE, w_param);
+            0
+        },
+        WM_MOVE => {
+            Win32WindowState::report(hwnd, CUSTOM_MOVE_MESSAGE, 0);
+            0
+        },
+        WM_DPICHANGED => {
+            // Windows suggests a rectangle keeping the logical size of the window, without applying it.
+            let rect = unsafe { &*(l_param as *const RECT) };
+            let flags = SWP_NOZORDER | SWP_NOACTIVATE;
+
+            unsafe {
+                SetWindowPos(hwnd, 0, rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top, flags);
+            }
+
+            Win32WindowState::report(hwnd, CUSTOM_SCALE_MESSAGE, w_param & 0xFFFF);
+            0
+        },
+        WM_DISPLAYCHANGE => {
+            Win32WindowState::report(hwnd, CUSTOM_DISPLAY_CHANGE_MESSAGE, 0);
+            0
+        },
+        WM_INPUT => {
+            // The input is freed by DefWindowProcW, so it is read here instead of in the message loop.
+            if let Some(state) = Win32WindowState::of(hwnd) {
+                if let Some((dx, dy)) = Window::raw_mouse_motion_win32(l_param) {
+                    state.emit(WindowEvent::Input(WindowInputEvent::RawMouseMotion { dx, dy }));
+                }
+            }
+
+            unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) }
+        },
+        WM_SETCURSOR if (l_param & 0xFFFF) as u32 == HTCLIENT => {
+            // DefWindowProcW would show the cursor of the window class.
+            let Some(state) = Win32WindowState::of(hwnd) else {
+                return unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) };
+            };
+
+            unsafe { SetCursor(state.cursor.get()); }
+            1
+        },
+        WM_SETFOCUS | WM_KILLFOCUS => {
+            Win32WindowState::report(hwnd, CUSTOM_FOCUS_MESSAGE, (msg == WM_SETFOCUS) as WPARAM);
+            0
+        },
+        WM_CLOSE => {
+            Win32WindowState::report(hwnd, CUSTOM_CLOSE_MESSAGE, 0);
+            0
+        },
+        CUSTOM_CLOSE_MESSAGE | CUSTOM_SIZE_MESSAGE | CUSTOM_CHAR_MESSAGE | CUSTOM_FOCUS_MESSAGE | CUSTOM_MOVE_MESSAGE
+            | CUSTOM_DISPLAY_CHANGE_MESSAGE | CUSTOM_SCALE_MESSAGE => {
+            if let Some(state) = Win32WindowState::of(hwnd) {
+                state.handle_custom_message(hwnd, msg, w_param);
+            }
+
+            0
+        },
+        WM_DESTROY => {
+            unsafe { PostQuitMessage(0); }
+            0
+        },
+        WM_SIZE if w_param as u32 != SIZE_MINIMIZED => {
+            Win32WindowState::report(hwnd, CUSTOM_SIZE_MESSAGE, 0);
+            0
+        },
+        WM_ENTERSIZEMOVE | WM_EXITSIZEMOVE => {
+            if let Some(state) = Win32WindowState::of(hwnd) {
+                state.in_size_move.set(msg == WM_ENTERSIZEMOVE);
+
+                // Reports the final size of an interactive resize.
+                if msg == WM_EXITSIZEMOVE {
+                    Win32WindowState::report(hwnd, CUSTOM_SIZE_MESSAGE, 0);
+                }
+            }
+
+            unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) }
+        },
+        _ => unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) },
+    }
+}
+
+impl Window {
+    /// Creates a new window at position (`x`, `y`), and name `window_name`.
+    ///
+    /// # Panics
+    /// Panics if the window could not be created. See [`Window::try_new`] for a non-panicking version.
+    pub fn new(
+        window_name: &str,
+        x: i32, y: i32,
+        width: i32, height: i32,
+    ) -> Self {
+        Self::try_new(window_name, x, y, width, height).unwrap_or_else(|e| {
+            log::error!("Window creation failed: {}", e);
+            panic!("Window creation failed: {}", e);
+        })
+    }
+
+    /// Creates a new window at position (`x`, `y`), and name `window_name`, returning an error if the window could
+    /// not be created.
+    pub fn try_new(
+        window_name: &str,
+        x: i32, y: i32,
+        width: i32, height: i32,
+    ) -> Result<Self, WindowError> {
+        let invalid_geometry = || WindowError::InvalidGeometry { x, y, width, height };
+
+        WindowBuilder::new()
+            .with_title(window_name)
+            .with_position(x, y)
+            .with_inner_size(PhysicalSize::new(
+                width.try_into().map_err(|_| invalid_geometry())?,
+                height.try_into().map_err(|_| invalid_geometry())?,
+            ))
+            .build()
+    }
+
+    /// Polls and parses system messages directed at the window and passes them on to the `event_closure` closure.
+    ///
+    /// # Panics
+    /// Panics if the connection to the display server fails. See [`Window::try_poll_messages`] for a non-panicking
+    /// version.
+    pub fn poll_messages(&mut self, event_closure: impl FnMut(WindowEvent)) {
+        if let Err(e) = self.try_poll_messages(event_closure) {
+            log::error!("Polling window messages failed: {}", e);
+            panic!("Polling window messages failed: {}", e);
+        }
+    }
+
+    /// Polls and parses system messages directed at the window and passes them on to the `event_closure` closure,
+    /// returning an error if the connection to the display server fails.
+    pub fn try_poll_messages(&mut self, mut event_closure: impl FnMut(WindowEvent)) -> Result<(), WindowError> {
+        #[cfg(target_os = "windows")]
+        let redraw_reported = {
+            self.poll_messages_win32(&mut event_closure)?;
+            self.take_modal_loop_damage_win32()
+        };
+
+        #[cfg(target_os = "linux")]
+        let redraw_reported = {
+            self.poll_messages_linux_x(&mut event_closure)?;
+            false
+        };
+
+        // Coalesce the redraw requests and damage of this call into a single event.
+        if !redraw_reported && (self.redraw_requested || !self.pending_damage.is_empty()) {
+            self.redraw_requested = false;
+            self.damage = std::mem::take(&mut self.pending_damage);
+
+            (event_closure)(WindowEvent::RedrawRequested);
+        }
+
+        Ok(())
+    }
+
+    /// Requests a [`WindowEvent::RedrawRequested`] event from the next [`Window::poll_messages`] call. Multiple
+    /// requests before that call result in a single event.
+    pub fn request_redraw(&mut self) {
+        self.redraw_requested = true;
+    }
+
+    /// Returns the parts of the window whose contents were lost, as of the last [`WindowEvent::RedrawRequested`]
+    /// event. Redrawing these suffices unless the redraw was requested with [`Window::request_redraw`]; empty if the
+    /// redraw was only requested.
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage
+    }
+
+    /// Adds `rect` to `pending_damage`, dropping the rectangles that cover each other.
+    fn add_damage(pending_damage: &mut Vec<Rect>, rect: Rect) {
+        if rect.width == 0 || rect.height == 0 || pending_damage.iter().any(|damage| damage.contains(&rect)) {
+            return;
+        }
+
+        pending_damage.retain(|damage| !rect.contains(damage));
+        pending_damage.push(rect);
+    }
+
+    /// Sets the title of the window.
+    pub fn set_title(&self, title: &str) {
+        #[cfg(target_os = "windows")]
+        { self.set_title_win32(title) }
+
+        #[cfg(target_os = "linux")]
+        { self.set_title_linux_x(title) }
+    }
+
+    /// Shows or hides the window.
+    pub fn set_visible(&mut self, visible: bool) {
+        #[cfg(target_os = "windows")]
+        unsafe { ShowWindow(self.hwnd, if visible { SW_SHOW } else { SW_HIDE }); }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.visible = visible;
+            self.set_visible_linux_x(visible);
+        }
+    }
+
+    /// Minimizes the window.
+    pub fn minimize(&self) {
+        #[cfg(target_os = "windows")]
+        unsafe { ShowWindow(self.hwnd, SW_MINIMIZE); }
+
+        #[cfg(target_os = "linux")]
+        { self.minimize_linux_x() }
+    }
+
+    /// Maximizes the window, or returns a maximized window to its normal size.
+    pub fn set_maximized(&self, maximized: bool) {
+        #[cfg(target_os = "windows")]
+        unsafe { ShowWindow(self.hwnd, if maximized { SW_MAXIMIZE } else { SW_RESTORE }); }
+
+        #[cfg(target_os = "linux")]
+        { self.set_maximized_linux_x(maximized) }
+    }
+
+    /// Returns a minimized or maximized window to its normal size and position.
+    pub fn restore(&self) {
+        #[cfg(target_os = "windows")]
+        unsafe { ShowWindow(self.hwnd, SW_RESTORE); }
+
+        #[cfg(target_os = "linux")]
+        { self.restore_linux_x() }
+    }
+
+    /// Returns the fullscreen mode last set with [`Window::set_fullscreen`] or [`WindowBuilder::with_fullscreen`]. The
+    /// user or window manager may have taken the window out of fullscreen since.
+    pub fn fullscreen(&self) -> Option<&Fullscreen> {
+        self.fullscreen.as_ref()
+    }
+
+    /// Makes the window fullscreen, or returns it to a normal window with `None`. Leaving exclusive fullscreen
+    /// switches the monitor back to the video mode it had before.
+    pub fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) {
+        if let Err(e) = self.change_fullscreen(fullscreen) {
+            log::warn!("Failed to change the fullscreen mode of the window: {}", e);
+        }
+    }
+
+    fn change_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> Result<(), WindowError> {
+        #[cfg(target_os = "windows")]
+        self.set_fullscreen_win32(fullscreen.as_ref())?;
+
+        #[cfg(target_os = "linux")]
+        self.set_fullscreen_linux_x(fullscreen.as_ref())?;
+
+        self.fullscreen = fullscreen;
+
+        Ok(())
+    }
+
+    /// Returns the monitors connected to the desktop, or none if they could not be queried.
+    pub fn available_monitors(&self) -> Vec<Monitor> {
+        #[cfg(target_os = "windows")]
+        { Self::monitors_win32() }
+
+        #[cfg(target_os = "linux")]
+        match self.monitors_linux_x() {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                log::warn!("Failed to query the monitors: {}", e);
+                Vec::new()
+            },
+        }
+    }
+
+    /// Returns the primary monitor, or `None` if it could not be queried. On X11 without a primary monitor
+    /// configured, this is the first monitor.
+    pub fn primary_monitor(&self) -> Option<Monitor> {
+        #[cfg(target_os = "windows")]
+        { Self::monitor_win32(unsafe { MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY) }) }
+
+        #[cfg(target_os = "linux")]
+        {
+            let mut monitors = self.available_monitors();
+            let index = monitors.iter().position(|monitor| monitor.primary).unwrap_or(0);
+
+            (index < monitors.len()).then(|| monitors.swap_remove(index))
+        }
+    }
+
+    /// Returns the monitor the window is on, the one showing the largest part of it, or `None` if it could not be
+    /// queried.
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        #[cfg(target_os = "windows")]
+        { Self::monitor_win32(unsafe { MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST) }) }
+
+        #[cfg(target_os = "linux")]
+        match self.current_monitor_linux_x() {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                log::warn!("Failed to query the monitor of the window: {}", e);
+                None
+            },
+        }
+    }
+
+    /// Returns whether the window has keyboard focus.
+    pub fn has_focus(&self) -> bool {
+        #[cfg(target_os = "windows")]
+        { unsafe { GetFocus() == self.hwnd } }
+
+        #[cfg(target_os = "linux")]
+        { self.has_focus_linux_x() }
+    }
+
+    /// Requests keyboard focus for the window. The request may be denied, such as by a window manager preventing
+    /// focus stealing, so wait for a [`WindowEvent::Focused`] event instead of assuming it succeeded.
+    pub fn focus(&self) {
+        #[cfg(target_os = "windows")]
+        unsafe { SetForegroundWindow(self.hwnd); }
+
+        #[cfg(target_os = "linux")]
+        { self.focus_linux_x() }
+    }
+
+    /// Returns the size of the client area of the window.
+    pub fn inner_size(&self) -> Result<PhysicalSize, WindowError> {
+        #[cfg(target_os = "windows")]
+        { Ok(self.inner_size_win32()) }
+
+        #[cfg(target_os = "linux")]
+        { self.inner_size_linux_x().map(|(width, height)| PhysicalSize::new(width, height)) }
+    }
+
+    /// Returns the factor by which the content of the window should be scaled, such as 2.0 when the system draws
+    /// everything twice as large. On X11, this is the `Xft/DPI` XSETTINGS setting or the `Xft.dpi` resource divided
+    /// by 96, or 1.0 if neither is set.
+    pub fn scale_factor(&self) -> f64 {
+        #[cfg(target_os = "windows")]
+        { self.dpi_win32() as f64 / 96.0 }
+
+        #[cfg(target_os = "linux")]
+        { self.xft_dpi.map_or(1.0, |dpi| dpi / 96.0) }
+    }
+
+    /// Returns the position of the top left corner of the window, including its decorations, on the desktop.
+    pub fn outer_position(&self) -> Result<(i32, i32), WindowError> {
+        #[cfg(target_os = "windows")]
+        { self.outer_position_win32() }
+
+        #[cfg(target_os = "linux")]
+        { self.outer_position_linux_x() }
+    }
+
+    /// Returns the position of the top left corner of the client area of the window on the desktop.
+    pub fn inner_position(&self) -> Result<(i32, i32), WindowError> {
+        #[cfg(target_os = "windows")]
+        { self.inner_position_win32() }
+
+        #[cfg(target_os = "linux")]
+        { self.inner_position_linux_x() }
+    }
+
+    /// Moves the top left corner of the window, including its decorations, to (`x`, `y`) on the desktop. The
+    /// window manager may override the position; a [`WindowEvent::Moved`] event reports where the window ended up.
+    pub fn set_outer_position(&self, x: i32, y: i32) {
+        #[cfg(target_os = "windows")]
+        { self.set_outer_position_win32(x, y) }
+
+        #[cfg(target_os = "linux")]
+        { self.set_outer_position_linux_x(x, y) }
+    }
+
+    /// Resizes the client area of the window to `size`. The window manager may override the size; a
+    /// [`WindowEvent::Resize`] event reports the size the window ended up with.
+    pub fn set_inner_size(&mut self, size: PhysicalSize) {
+        #[cfg(target_os = "windows")]
+        { self.set_inner_size_win32(size.width, size.height) }
+
+        #[cfg(target_os = "linux")]
+        if let Err(e) = self.set_inner_size_linux_x(size.width, size.height) {
+            log::warn!("Failed to resize the window: {}", e);
+        }
+    }
+
+    /// Sets the size the user cannot resize the client area below, or removes the limit with `None`.
+    pub fn set_min_inner_size(&mut self, size: Option<PhysicalSize>) {
+        let size = size.map(|size| (size.width, size.height));
+
+        #[cfg(target_os = "windows")]
+        {
+            self.state.min_inner_size.set(size);
+            self.apply_size_constraints_win32();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.size_hints.min_size = size;
+            self.apply_size_hints_linux_x();
+        }
+    }
+
+    /// Sets the size the user cannot resize the client area above, or removes the limit with `None`.
+    pub fn set_max_inner_size(&mut self, size: Option<PhysicalSize>) {
+        let size = size.map(|size| (size.width, size.height));
+
+        #[cfg(target_os = "windows")]
+        {
+            self.state.max_inner_size.set(size);
+            self.apply_size_constraints_win32();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.size_hints.max_size = size;
+            self.apply_size_hints_linux_x();
+        }
+    }
+
+    /// Makes the user resize the client area in steps of `increments`, counted from the minimum size, or in single
+    /// pixels with `None`. Useful for terminals and other windows laid out on a grid.
+    pub fn set_resize_increments(&mut self, increments: Option<PhysicalSize>) {
+        let increments = increments.map(|increments| (increments.width, increments.height));
+
+        #[cfg(target_os = "windows")]
+        { self.state.resize_increments.set(increments); }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.size_hints.resize_increments = increments;
+            self.apply_size_hints_linux_x();
+        }
+    }
+
+    /// Keeps the width to height ratio of the client area at `ratio` while the user resizes the window, or lets the
+    /// user resize freely with `None`.
+    pub fn set_aspect_ratio(&mut self, ratio: Option<(u32, u32)>) {
+        #[cfg(target_os = "windows")]
+        { self.state.aspect_ratio.set(ratio); }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.size_hints.aspect_ratio = ratio;
+            self.apply_size_hints_linux_x();
+        }
+    }
+
+    /// Returns the position of the cursor relative to the top left corner of the client area of the window. The
+    /// position lies outside of the window when the cursor does. Returns `None` if the position could not be queried,
+    /// such as when the cursor is on another X screen, or does not fit in an `i16`.
+    pub fn cursor_position(&self) -> Option<(i16, i16)> {
+        #[cfg(target_os = "windows")]
+        { self.cursor_position_win32() }
+
+        #[cfg(target_os = "linux")]
+        { self.cursor_position_linux_x() }
+    }
+
+    /// Keeps the cursor inside the client area of the window, or in place, while the window has focus. The grab is
+    /// released while the window does not have focus, and taken again when it regains it. While the cursor is locked,
+    /// no [`WindowInputEvent::MouseMove`] events are sent.
+    pub fn set_cursor_grab(&mut self, mode: CursorGrabMode) {
+        if mode == self.cursor_grab {
+            return;
+        }
+
+        self.cursor_grab = mode;
+
+        #[cfg(target_os = "windows")]
+        { self.update_cursor_grab_win32() }
+
+        #[cfg(target_os = "linux")]
+        if let Err(e) = self.update_cursor_grab_linux_x() {
+            log::warn!("Failed to grab the cursor: {}", e);
+        }
+    }
+
+    /// Shows or hides the cursor while it is over the client area of the window.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        if visible == self.cursor_visible {
+            return;
+        }
+
+        self.cursor_visible = visible;
+
+        #[cfg(target_os = "windows")]
+        { self.update_cursor_win32() }
+
+        #[cfg(target_os = "linux")]
+        if let Err(e) = self.update_cursor_linux_x() {
+            log::warn!("Failed to change the cursor visibility: {}", e);
+        }
+    }
+
+    /// Sets the cursor shown over the client area of the window. A hidden cursor stays hidden, and shows the icon
+    /// once [`Window::set_cursor_visible`] shows it again.
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        if icon == self.cursor_icon {
+            return;
+        }
+
+        self.cursor_icon = icon;
+
+        if !self.cursor_visible {
+            return;
+        }
+
+        #[cfg(target_os = "windows")]
+        { self.update_cursor_win32() }
+
+        #[cfg(target_os = "linux")]
+        if let Err(e) = self.update_cursor_linux_x() {
+            log::warn!("Failed to change the cursor icon: {}", e);
+        }
+    }
+
+    /// Enables or disables the [`WindowInputEvent::RawMouseMotion`] events. On Windows, raw input goes to a single
+    /// window of the process, the last one to enable it.
+    pub fn set_raw_input(&mut self, enabled: bool) {
+        #[cfg(target_os = "windows")]
+        { self.set_raw_input_win32(enabled) }
+
+        #[cfg(target_os = "linux")]
+        if let Err(e) = self.set_raw_input_linux_x(enabled) {
+            log::warn!("Failed to change the raw input of the window: {}", e);
+        }
+    }
+
+    /// Returns the modifier keys currently held, and the lock keys currently active.
+    pub fn modifiers(&self) -> Modifiers {
+        #[cfg(target_os = "windows")]
+        { Self::modifiers_win32() }
+
+        #[cfg(target_os = "linux")]
+        { self.modifiers_linux_x() }
+    }
+
+    /// Returns the key the current keyboard layout produces for the key at `scancode`.
+    pub fn key_for_scancode(&self, scancode: ScanCode) -> Option<Keys> {
+        #[cfg(target_os = "windows")]
+        { Self::key_for_scancode_win32(scancode) }
+
+        #[cfg(target_os = "linux")]
+        { self.key_for_scancode_linux_x(scancode) }
+    }
+
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        #[cfg(target_os = "windows")]
+        { self.raw_window_handle_win32() }
+
+        #[cfg(target_os = "linux")]
+        { self.raw_window_handle_linux_x() }
+    }
+
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        #[cfg(target_os = "windows")]
+        { self.raw_display_handle_windows() }
+
+        #[cfg(target_os = "linux")]
+        { self.raw_display_handle_linux_x() }
+    }
+}
+
+/// A builder for the creation attributes of a [`Window`].
+///
+/// # Examples
+/// ```no_run
+/// use simple_window::{PhysicalSize, WindowBuilder};
+///
+/// let window = WindowBuilder::new()
+///     .with_title("Tool Palette")
+///     .with_inner_size(PhysicalSize::new(300, 500))
+///     .with_min_inner_size(PhysicalSize::new(200, 300))
+///     .with_always_on_top(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct WindowBuilder {
+    title: String,
+    position: Option<(i32, i32)>,
+    inner_size: (u32, u32),
+    min_inner_size: Option<(u32, u32)>,
+    max_inner_size: Option<(u32, u32)>,
+    resizable: bool,
+    decorated: bool,
+    visible: bool,
+    maximized: bool,
+    fullscreen: Option<Fullscreen>,
+    always_on_top: bool,
+    transparent: bool,
+    window_class: Option<String>,
+}
+
+impl Default for WindowBuilder {
+    fn default() -> Self {
+        Self {
+            title: String::from("Window"),
+            position: None,
+            inner_size: (800, 600),
+            min_inner_size: None,
+            max_inner_size: None,
+            resizable: true,
+            decorated: true,
+            visible: true,
+            maximized: false,
+            fullscreen: None,
+            always_on_top: false,
+            transparent: false,
+            window_class: None,
+        }
+    }
+}
+
+impl WindowBuilder {
+    /// Creates a builder with the default attributes: a visible, decorated and resizable 800x600 window.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the window title.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_owned();
+        self
+    }
+
+    /// Sets the initial position of the window. Without a position, the window manager places the window.
+    pub fn with_position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Sets the initial size of the window's client area.
+    pub fn with_inner_size(mut self, size: PhysicalSize) -> Self {
+        self.inner_size = (size.width, size.height);
+        self
+    }
+
+    /// Sets the size the client area cannot be resized below.
+    pub fn with_min_inner_size(mut self, size: PhysicalSize) -> Self {
+        self.min_inner_size = Some((size.width, size.height));
+        self
+    }
+
+    /// Sets the size the client area cannot be resized above.
+    pub fn with_max_inner_size(mut self, size: PhysicalSize) -> Self {
+        self.max_inner_size = Some((size.width, size.height));
+        self
+    }
+
+    /// Sets whether the user can resize the window.
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Sets whether the window has a title bar and border.
+    pub fn with_decorations(mut self, decorated: bool) -> Self {
+        self.decorated = decorated;
+        self
+    }
+
+    /// Sets whether the window is shown as soon as it is created.
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Sets whether the window starts maximized.
+    pub fn with_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = maximized;
+        self
+    }
+
+    /// Sets the fullscreen mode the window starts in, if any.
+    pub fn with_fullscreen(mut self, fullscreen: Option<Fullscreen>) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Sets whether the window is kept above other windows.
+    pub fn with_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// Sets whether the window background is transparent, letting the alpha channel of the rendered content through.
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Sets the window class: `WM_CLASS` on X11, and the registered window class name on Windows.
+    pub fn with_window_class(mut self, window_class: &str) -> Self {
+        self.window_class = Some(window_class.to_owned());
+        self
+    }
+
+    /// Creates the window.
+    pub fn build(&self) -> Result<Window, WindowError> {
+        #[cfg(target_os = "windows")]
+        let mut window = Window::new_win32(self)?;
+
+        #[cfg(target_os = "linux")]
+        let mut window = Window::new_linux_x(self)?;
+
+        // The window starts out covering the monitor, but switching the video mode needs the window to exist.
+        if let Some(Fullscreen::Exclusive(_)) = &self.fullscreen {
+            window.change_fullscreen(self.fullscreen.clone())?;
+        }
+
+        Ok(window)
+    }
+
+    fn invalid_geometry(&self) -> WindowError {
+        let (x, y) = self.position.unwrap_or((0, 0));
+
+        WindowError::InvalidGeometry {
+            x, y,
+            width: self.inner_size.0.try_into().unwrap_or(i32::MAX),
+            height: self.inner_size.1.try_into().unwrap_or(i32::MAX),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Window {
+    fn new_linux_x(builder: &WindowBuilder) -> Result<Self, WindowError> {
+        // The window manager makes a fullscreen window cover the monitor it is placed on.
+        let (x, y) = match &builder.fullscreen {
+            Some(Fullscreen::Borderless(Some(monitor))) => monitor.position,
+            _ => builder.position.unwrap_or((0, 0)),
+        };
+        let x: i16 = x.try_into().map_err(|_| builder.invalid_geometry())?;
+        let y: i16 = y.try_into().map_err(|_| builder.invalid_geometry())?;
+        let width: u16 = builder.inner_size.0.try_into().ok().filter(|&w| w > 0)
+            .ok_or_else(|| builder.invalid_geometry())?;
+        let height: u16 = builder.inner_size.1.try_into().ok().filter(|&h| h > 0)
+            .ok_or_else(|| builder.invalid_geometry())?;
+
+        let (conn, screen_num) = xcb::Connection::connect_with_xlib_display_and_extensions(
+            &[], &[xcb::Extension::RandR, xcb::Extension::Input]
+        )?;
+
+        // Events are read through xcb. Without this, Xlib calls could move events into the Xlib queue.
+        conn.set_event_queue_owner(xcb::EventQueueOwner::Xcb);
+
+        let setup = conn.get_setup();
+        let screen = setup.roots().nth(screen_num as usize).ok_or(xcb::ConnError::ClosedInvalidScreen)?;
+
+        // A transparent window needs a 32 bit visual with an alpha channel, and a colormap to go with it.
+        let argb_visual = if builder.transparent {
+            let visual = screen.allowed_depths()
+                .filter(|depth| depth.depth() == 32)
+                .flat_map(|depth| depth.visuals().iter())
+                .find(|visual| visual.class() == x::VisualClass::TrueColor)
+                .map(|visual| visual.visual_id());
+
+            if visual.is_none() {
+                log::warn!("No 32 bit visual available, creating an opaque window instead.");
+            }
+
+            visual
+        } else {
+            None
+        };
+
+        let event_mask = x::EventMask::BUTTON_PRESS | x::EventMask::BUTTON_RELEASE | x::EventMask::KEY_PRESS
+            | x::EventMask::KEY_RELEASE | x::EventMask::EXPOSURE | x::EventMask::POINTER_MOTION
+            | x::EventMask::STRUCTURE_NOTIFY | x::EventMask::FOCUS_CHANGE | x::EventMask::ENTER_WINDOW
+            | x::EventMask::LEAVE_WINDOW | x::EventMask::PROPERTY_CHANGE;
+
+        let window: x::Window = conn.generate_id();
+
+        let cookie = if let Some(visual) = argb_visual {
+            let colormap: x::Colormap = conn.generate_id();
+
+            conn.check_request(conn.send_request_checked(&x::CreateColormap {
+                alloc: x::ColormapAlloc::None,
+                mid: colormap,
+                window: screen.root(),
+                visual,
+            }))?;
+
+            let cookie = conn.send_request_checked(&x::CreateWindow {
+                depth: 32,
+                wid: window,
+                parent: screen.root(),
+                x,
+                y,
+                width,
+                height,
+                border_width: 0,
+                class: x::WindowClass::InputOutput,
+                visual,
+                value_list: &[
+                    x::Cw::BackPixel(0),
+                    x::Cw::BorderPixel(0),
+                    x::Cw::EventMask(event_mask),
+                    x::Cw::Colormap(colormap),
+                ],
+            });
+
+            // The window keeps its own reference to the colormap.
+            conn.send_request(&x::FreeColormap { cmap: colormap });
+
+            cookie
+        } else {
+            conn.send_request_checked(&x::CreateWindow {
+                depth: x::COPY_FROM_PARENT as u8,
+                wid: window,
+                parent: screen.root(),
+                x,
+                y,
+                width,
+                height,
+                border_width: 0,
+                class: x::WindowClass::InputOutput,
+                visual: screen.root_visual(),
+                value_list: &[
+                    x::Cw::BackPixel(screen.white_pixel()),
+                    x::Cw::EventMask(event_mask),
+                ],
+            })
+        };
+        conn.check_request(cookie)?;
+
+        let atoms = XAtoms::intern(&conn)?;
+
+        let xsettings_selection = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+            only_if_exists: false,
+            name: format!("_XSETTINGS_S{}", screen_num).as_bytes(),
+        }))?.atom();
+
+        // Changes of the X resources are announced on the root window, and so is the start of an XSETTINGS manager,
+        // through a MANAGER client message.
+        conn.send_request(&x::ChangeWindowAttributes {
+            window: screen.root(),
+            value_list: &[x::Cw::EventMask(x::EventMask::PROPERTY_CHANGE | x::EventMask::STRUCTURE_NOTIFY)],
+        });
+
+        // The server limits RandR to its oldest version for clients that do not announce the version they support.
+        let randr_version = if conn.active_extensions().any(|extension| extension == xcb::Extension::RandR) {
+            let reply = conn.wait_for_reply(conn.send_request(&randr::QueryVersion {
+                major_version: 1,
+                minor_version: 5,
+            }))?;
+
+            // Changes of the monitors and their configuration are announced on the root window.
+            conn.send_request(&randr::SelectInput {
+                window: screen.root(),
+                enable: randr::NotifyMask::SCREEN_CHANGE | randr::NotifyMask::CRTC_CHANGE
+                    | randr::NotifyMask::OUTPUT_CHANGE,
+            });
+
+            (reply.major_version(), reply.minor_version())
+        } else {
+            (0, 0)
+        };
+
+        // XInput 2.1 reports the scrolling of touchpads and high resolution wheels through scroll valuators. Selecting
+        // its button and motion events stops the core events of the window.
+        let xinput2 = conn.active_extensions().any(|extension| extension == xcb::Extension::Input) && {
+            let reply = conn.wait_for_reply(conn.send_request(&xinput::XiQueryVersion {
+                major_version: 2,
+                minor_version: 1,
+            }))?;
+
+            (reply.major_version(), reply.minor_version()) >= (2, 1)
+        };
+
+        if xinput2 {
+            conn.send_request(&xinput::XiSelectEvents {
+                window,
+                masks: &[xinput::EventMaskBuf::new(xinput::Device::AllMaster, &[
+                    xinput::XiEventMask::BUTTON_PRESS | xinput::XiEventMask::BUTTON_RELEASE
+                        | xinput::XiEventMask::MOTION | xinput::XiEventMask::DEVICE_CHANGED,
+                ])],
+            });
+        }
+
+        Self::write_title_linux_x(&conn, window, &atoms, &builder.title);
+
+        if let Some(window_class) = &builder.window_class {
+            // WM_CLASS holds the instance name followed by the class name, both null terminated.
+            let data = [window_class.as_bytes(), b"\0", window_class.as_bytes(), b"\0"].concat();
+
+            conn.check_request(conn.send_request_checked(&x::ChangeProperty {
+                mode: x::PropMode::Replace,
+                window,
+                property: x::ATOM_WM_CLASS,
+                r#type: x::ATOM_STRING,
+                data: &data,
+            }))?;
+        }
+
+        let size_hints = utility::WmSizeHints {
+            min_size: builder.min_inner_size,
+            max_size: builder.max_inner_size,
+            ..Default::default()
+        };
+
+        conn.check_request(conn.send_request_checked(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window,
+            property: x::ATOM_WM_NORMAL_HINTS,
+            r#type: x::ATOM_WM_SIZE_HINTS,
+            data: &Self::effective_size_hints(size_hints, builder.resizable, builder.inner_size, builder.position)
+                .to_wire(),
+        }))?;
+
+        // Get atoms.
+        let (wm_protocols, wm_del_window) = {
+            let cookies = (
+                conn.send_request(&x::InternAtom {
+                    only_if_exists: true,
+                    name: b"WM_PROTOCOLS",
+                }),
+                conn.send_request(&x::InternAtom {
+                    only_if_exists: true,
+                    name: b"WM_DELETE_WINDOW",
+                }),
+            );
+
+            (
+                Self::existing_atom(conn.wait_for_reply(cookies.0)?.atom(), "WM_PROTOCOLS")?,
+                Self::existing_atom(conn.wait_for_reply(cookies.1)?.atom(), "WM_DELETE_WINDOW")?,
+            )
+        };
+
+        conn.check_request(conn.send_request_checked(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window,
+            property: wm_protocols,
+            r#type: x::ATOM_ATOM,
+            data: &[wm_del_window],
+        }))?;
+
+        if !builder.decorated {
+            conn.check_request(conn.send_request_checked(&x::ChangeProperty {
+                mode: x::PropMode::Replace,
+                window,
+                property: atoms.motif_wm_hints,
+                r#type: atoms.motif_wm_hints,
+                data: &utility::motif_wm_hints(false),
+            }))?;
+        }
+
+        // The window manager reads the initial state from _NET_WM_STATE when the window is mapped.
+        let mut state = Vec::new();
+
+        if builder.maximized {
+            state.extend([atoms.net_wm_state_maximized_vert, atoms.net_wm_state_maximized_horz]);
+        }
+
+        if builder.fullscreen.is_some() {
+            state.push(atoms.net_wm_state_fullscreen);
+        }
+
+        if builder.always_on_top {
+            state.push(atoms.net_wm_state_above);
+        }
+
+        if !state.is_empty() {
+            conn.check_request(conn.send_request_checked(&x::ChangeProperty {
+                mode: x::PropMode::Replace,
+                window,
+                property: atoms.net_wm_state,
+                r#type: x::ATOM_ATOM,
+                data: &state,
+            }))?;
+        }
+
+        if builder.visible {
+            conn.send_request(&x::MapWindow {
+                window,
+            });
+        }
+
+        conn.flush()?;
+
+        // Makes the server report auto repeat as repeated presses, instead of release and press pairs.
+        let mut detectable_auto_repeat = 0;
+        unsafe { x11::xlib::XkbSetDetectableAutoRepeat(conn.get_raw_dpy(), 1, &mut detectable_auto_repeat); }
+
+        let input_context = XInputContext::new(conn.get_raw_dpy(), window);
+
+        if input_context.is_none() {
+            log::warn!("Failed to open an X input method, text input events will not be sent.");
+        }
+
+        let cursor_context = XCursorContext::new(&conn, screen_num);
+
+        if cursor_context.is_none() {
+            log::warn!("Failed to load libxcb-cursor or the Xcursor theme, using the X core cursors instead.");
+        }
+
+        let mut window = Self {
+            previous_position: (0, 0),
+            redraw_requested: false,
+            pending_damage: Vec::new(),
+            damage: Vec::new(),
+            fullscreen: builder.fullscreen.clone(),
+            click_counter: ClickCounter::default(),
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            cursor_icon: CursorIcon::Arrow,
+            previous_size: (0, 0),
+            interactive_resize: false,
+            connection: conn,
+            screen: screen_num,
+            window,
+            wm_del_window,
+            atoms,
+            input_context,
+            cursor_context,
+            detectable_auto_repeat: detectable_auto_repeat != 0,
+            pressed_keys: HashSet::new(),
+            pending_event: None,
+            size_hints,
+            resizable: builder.resizable,
+            randr_version,
+            xsettings_selection,
+            xsettings_owner: x::Window::none(),
+            xft_dpi: None,
+            double_click: (0, 0),
+            saved_crtc: None,
+            xinput2,
+            focused: false,
+            locked_cursor_position: None,
+            pointer_grabbed: false,
+            scroll_valuators: HashMap::new(),
+            cursor_position: None,
+            frame_extents: (0, 0),
+            visible: builder.visible,
+            reparented: false,
+        };
+
+        window.xsettings_owner = window.watch_xsettings_linux_x()?;
+        window.xft_dpi = window.read_xft_dpi_linux_x()?;
+        window.double_click = window.read_double_click_linux_x();
+        window.frame_extents = window.frame_extents_linux_x()?;
+
+        Ok(window)
+    }
+
+    /// Interns all atoms in `names`, creating the ones the server does not know yet.
+    fn intern_atoms<const N: usize>(
+        conn: &xcb::Connection,
+        names: [&'static str; N],
+    ) -> Result<[x::Atom; N], WindowError> {
+        let cookies = names.map(|name| conn.send_request(&x::InternAtom {
+            only_if_exists: false,
+            name: name.as_bytes(),
+        }));
+
+        let mut atoms = [x::ATOM_NONE; N];
+
+        for (atom, cookie) in atoms.iter_mut().zip(cookies) {
+            *atom = conn.wait_for_reply(cookie)?.atom();
+        }
+
+        Ok(atoms)
+    }
+
+    /// Returns `atom`, or a [`WindowError::MissingAtom`] if the server did not know the atom `name`.
+    fn existing_atom(atom: x::Atom, name: &'static str) -> Result<x::Atom, WindowError> {
+        if atom == x::ATOM_NONE {
+            Err(WindowError::MissingAtom(name))
+        } else {
+            Ok(atom)
+        }
+    }
+
+    fn poll_messages_linux_x(&mut self, mut event_closure: impl FnMut(WindowEvent)) -> Result<(), WindowError> {
+        let mut monitors_changed = false;
+        // Whether a mouse button is held, queried at most once per call as the query waits for the server.
+        let mut button_held = None;
+
+        while let Some(event) = self.next_event_linux_x()? {
+            // A single change of the monitors comes with a batch of events, reported together after the loop.
+            if let xcb::Event::RandR(event) = &event {
+                monitors_changed |= match event {
+                    randr::Event::ScreenChangeNotify(_) => true,
+                    randr::Event::Notify(event) => {
+                        matches!(event.sub_code(), randr::Notify::CrtcChange | randr::Notify::OutputChange)
+                    },
+                };
+            }
+
+            // A grab refused at FocusIn, as the window manager still held the pointer, is retried once the pointer
+            // interacts with the window.
+            let grab_refused = self.cursor_grab != CursorGrabMode::None && self.focused && !self.pointer_grabbed;
+
+            if grab_refused && Self::is_pointer_event(&event) {
+                if let Err(e) = self.update_cursor_grab_linux_x() {
+                    log::warn!("Failed to grab the cursor: {}", e);
+                }
+            }
+
+            let event = match event {
+                xcb::Event::Input(event) => {
+                    self.handle_xinput_event_linux_x(event, &mut event_closure)?;
+                    continue;
+                },
+                event => event,
+            };
+
+            if let xcb::Event::X(event) = event { match event {
+                    x::Event::KeyPress(event) => {
+                        // A press of a key that is still held down is an auto repeat.
+                        let repeat = !self.pressed_keys.insert(event.detail());
+
+                        let key = self.translate_key_code(event.detail(), event.state());
+                        let scancode = Self::translate_scancode(event.detail());
+                        // The state holds the modifiers from before the event, so add the pressed key.
+                        let modifiers = Self::translate_modifiers(event.state())
+                            | self.key_modifier_linux_x(event.detail(), event.state());
+
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::KeyDown {
+                            key, scancode, modifiers, repeat
+                        }));
+
+                        if let Some(text) = self.lookup_text(&event) {
+                            (event_closure)(WindowEvent::Input(WindowInputEvent::Text(text)));
+                        }
+                    },
+                    x::Event::KeyRelease(event) => {
+                        if !self.detectable_auto_repeat {
+                            // Without detectable auto repeat, a repeat is a release directly followed by a press of
+                            // the same key with the same time. The key stays pressed, so the press counts as repeat.
+                            match self.connection.poll_for_event()? {
+                                Some(xcb::Event::X(x::Event::KeyPress(next)))
+                                    if next.detail() == event.detail() && next.time() == event.time() => {
+                                    self.pending_event = Some(xcb::Event::X(x::Event::KeyPress(next)));
+                                    continue;
+                                },
+                                next => self.pending_event = next,
+                            }
+                        }
+
+                        self.pressed_keys.remove(&event.detail());
+
+                        let key = self.translate_key_code(event.detail(), event.state());
+                        let scancode = Self::translate_scancode(event.detail());
+                        let modifiers = self.release_modifiers_linux_x(event.detail(), event.state());
+
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::KeyUp { key, scancode, modifiers }));
+                    },
+                    x::Event::FocusIn(event) if Self::is_focus_change(event.mode(), event.detail()) => {
+                        self.focused = true;
+
+                        if let Some(input_context) = &self.input_context {
+                            unsafe { x11::xlib::XSetICFocus(input_context.ic); }
+                        }
+
+                        if let Err(e) = self.update_cursor_grab_linux_x() {
+                            log::warn!("Failed to grab the cursor: {}", e);
+                        }
+
+                        (event_closure)(WindowEvent::Focused(true));
+                    },
+                    x::Event::FocusOut(event) if Self::is_focus_change(event.mode(), event.detail()) => {
+                        // Keys released while unfocused send no release events.
+                        self.pressed_keys.clear();
+                        self.focused = false;
+
+                        if let Some(input_context) = &self.input_context {
+                            unsafe { x11::xlib::XUnsetICFocus(input_context.ic); }
+                        }
+
+                        if let Err(e) = self.update_cursor_grab_linux_x() {
+                            log::warn!("Failed to release the cursor: {}", e);
+                        }
+
+                        (event_closure)(WindowEvent::Focused(false));
+                    },
+                    x::Event::ButtonPress(event) => {
+                        let modifiers = Self::translate_modifiers(event.state());
+                        let press = Some((event.time(), (event.event_x(), event.event_y())));
+
+                        self.button_event_linux_x(event.detail() as u32, press, modifiers, &mut event_closure);
+                    },
+                    x::Event::ButtonRelease(event) => {
+                        let modifiers = Self::translate_modifiers(event.state());
+
+                        self.button_event_linux_x(event.detail() as u32, None, modifiers, &mut event_closure);
+                    },
+                    x::Event::MotionNotify(event) => {
+                        let x = event.event_x();
+                        let y = event.event_y();
+                        let modifiers = Self::translate_modifiers(event.state());
+
+                        if !self.hold_locked_cursor_linux_x((x, y)) {
+                            (event_closure)(WindowEvent::Input(WindowInputEvent::MouseMove { x, y, modifiers }));
+                        }
+                    },
+                    x::Event::EnterNotify(event) if Self::is_crossing(event.mode(), event.detail()) => {
+                        // The scroll valuators changed while other windows received the events.
+                        self.scroll_valuators.clear();
+                        self.cursor_position = None;
+
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::CursorEntered));
+                    },
+                    x::Event::LeaveNotify(event) if Self::is_crossing(event.mode(), event.detail()) => {
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::CursorLeft));
+                    },
+                    x::Event::Expose(event) => {
+                        Self::add_damage(&mut self.pending_damage, Rect {
+                            x: event.x() as i32,
+                            y: event.y() as i32,
+                            width: event.width() as u32,
+                            height: event.height() as u32,
+                        });
+                    },
+                    x::Event::ConfigureNotify(event) if event.window() == self.window => {
+                        // Window resize. Also triggered by window move.
+
+                        let width = event.width() as u32;
+                        let height = event.height() as u32;
+
+                        if self.previous_size != (width, height) {
+                            self.previous_size = (width, height);
+
+                            // X11 does not announce interactive resizes. While the user drags the border, the window
+                            // manager holds the button the drag started with.
+                            let interactive = *button_held.get_or_insert_with(|| self.pointer_button_held_linux_x());
+                            self.interactive_resize |= interactive;
+
+                            let size = PhysicalSize::new(width, height);
+                            (event_closure)(WindowEvent::Resize { size, interactive });
+                        }
+
+                        // Reparenting window managers move the frame instead of the window, which leaves the position
+                        // in the real event relative to the frame. The synthetic events they send hold the position
+                        // in root coordinates, so the real events are skipped instead of querying the position.
+                        if event.is_from_send_event() || !self.reparented {
+                            let (left, top) = self.frame_extents;
+                            let position = (event.x() as i32 - left, event.y() as i32 - top);
+
+                            if self.previous_position != position {
+                                self.previous_position = position;
+
+                                (event_closure)(WindowEvent::Moved(position.0, position.1));
+                            }
+                        }
+                    },
+                    x::Event::ReparentNotify(event) if event.window() == self.window => {
+                        self.reparented = event.parent() != self.root_linux_x();
+                    },
+                    x::Event::PropertyNotify(event)
+                        if event.window() == self.window && event.atom() == self.atoms.net_frame_extents => {
+                        match self.frame_extents_linux_x() {
+                            Ok(frame_extents) => self.frame_extents = frame_extents,
+                            Err(e) => log::warn!("Failed to read the frame extents: {}", e),
+                        }
+                    },
+                    x::Event::ClientMessage(event) => {
+                        if let x::ClientMessageData::Data32([first, second, ..]) = event.data() {
+                            if event.window() == self.window && first == self.wm_del_window.resource_id() {
+                                (event_closure)(WindowEvent::Close);
+                            } else if event.r#type() == self.atoms.manager
+                                && second == self.xsettings_selection.resource_id() {
+                                // A new XSETTINGS manager took over.
+                                self.xsettings_owner = self.watch_xsettings_linux_x()?;
+                                self.update_settings_linux_x(&mut event_closure)?;
+                            }
+                        }
+                    },
+                    x::Event::PropertyNotify(event) if self.is_settings_change(&event) => {
+                        self.update_settings_linux_x(&mut event_closure)?;
+                    },
+                    x::Event::DestroyNotify(event) if event.window() == self.xsettings_owner => {
+                        self.xsettings_owner = self.watch_xsettings_linux_x()?;
+                        self.update_settings_linux_x(&mut event_closure)?;
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        if monitors_changed {
+            (event_closure)(WindowEvent::MonitorsChanged);
+        }
+
+        // The window manager grabs the pointer during the drag, so the release of the button is never reported.
+        if self.interactive_resize && !button_held.unwrap_or_else(|| self.pointer_button_held_linux_x()) {
+            self.interactive_resize = false;
+
+            let (width, height) = self.previous_size;
+            (event_closure)(WindowEvent::Resize { size: PhysicalSize::new(width, height), interactive: false });
+        }
+
+        Ok(())
+    }
+
+    /// Reports the press at the time and position `press`, or else the release, of the X11 mouse button `detail`. The
+    /// wheel sends a press and a release for every notch, reported as a single scroll.
+    fn button_event_linux_x(
+        &mut self,
+        detail: u32,
+        press: Option<(x::Timestamp, (i16, i16))>,
+        modifiers: Modifiers,
+        event_closure: &mut impl FnMut(WindowEvent),
+    ) {
+        let button = match detail as c_uint {
+            x11::xlib::Button1 => MouseButton::Left,
+            x11::xlib::Button2 => MouseButton::Middle,
+            x11::xlib::Button3 => MouseButton::Right,
+            x11::xlib::Button4 | x11::xlib::Button5 | 6 | 7 => {
+                if press.is_none() {
+                    let (delta_x, delta_y) = match detail as c_uint {
+                        x11::xlib::Button4 => (0.0, 1.0),
+                        x11::xlib::Button5 => (0.0, -1.0),
+                        6 => (-1.0, 0.0),
+                        _ => (1.0, 0.0),
+                    };
+
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseWheel {
+                        delta_x, delta_y, unit: ScrollUnit::Lines
+                    }));
+                }
+
+                return;
+            },
+            8 => MouseButton::Back,
+            9 => MouseButton::Forward,
+            _ => MouseButton::Other(detail as u16),
+        };
+
+        if let Some((time, position)) = press {
+            let (max_time, max_distance) = self.double_click;
+            let click_count = self.click_counter.press(button, time, position, max_time, (max_distance, max_distance));
+
+            (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown { button, modifiers, click_count }));
+        } else {
+            (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp { button, modifiers }));
+        }
+    }
+
+    /// Handles the XInput 2 events: the button and motion events selected on the window, which replace its core
+    /// events, and the raw motion selected on the root window.
+    fn handle_xinput_event_linux_x(
+        &mut self,
+        event: xinput::Event,
+        event_closure: &mut impl FnMut(WindowEvent),
+    ) -> Result<(), WindowError> {
+        // The server also sends wheel button presses for the scrolling reported by the scroll valuators.
+        let is_scroll_emulation = |event: &xinput::ButtonPressEvent| {
+            (4..=7).contains(&event.detail())
+                && event.flags().contains(xinput::PointerEventFlags::POINTER_EMULATED)
+        };
+
+        match event {
+            xinput::Event::ButtonPress(event) if !is_scroll_emulation(&event) => {
+                let modifiers = Self::translate_xinput_modifiers(event.mods());
+                let press = Some((event.time(), Self::xinput_position(&event)));
+
+                self.button_event_linux_x(event.detail(), press, modifiers, event_closure);
+            },
+            xinput::Event::ButtonRelease(event) if !is_scroll_emulation(&event) => {
+                let modifiers = Self::translate_xinput_modifiers(event.mods());
+
+                self.button_event_linux_x(event.detail(), None, modifiers, event_closure);
+            },
+            xinput::Event::Motion(event) => {
+                // The server sends motion for every change of a valuator, scrolling included.
+                let position = Self::xinput_position(&event);
+
+                if !self.hold_locked_cursor_linux_x(position) && self.cursor_position != Some(position) {
+                    self.cursor_position = Some(position);
+
+                    let (x, y) = position;
+                    let modifiers = Self::translate_xinput_modifiers(event.mods());
+
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseMove { x, y, modifiers }));
+                }
+
+                self.scroll_event_linux_x(&event, event_closure)?;
+            },
+            xinput::Event::DeviceChanged(event) => {
+                self.scroll_valuators.remove(&event.source().id());
+            },
+            // Raw events are selected on the root window, and arrive whichever window has focus.
+            xinput::Event::RawMotion(event) if self.focused => {
+                let (mut dx, mut dy) = (0.0, 0.0);
+
+                for (number, value) in utility::valuator_values(event.valuator_mask(), event.axisvalues_raw()) {
+                    match number {
+                        0 => dx = value,
+                        1 => dy = value,
+                        _ => {},
+                    }
+                }
+
+                if dx != 0.0 || dy != 0.0 {
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::RawMouseMotion { dx, dy }));
+                }
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    /// Returns the position of the cursor in the window in an XInput 2 button or motion event, in whole pixels.
+    fn xinput_position(event: &xinput::ButtonPressEvent) -> (i16, i16) {
+        ((event.event_x() >> 16) as i16, (event.event_y() >> 16) as i16)
+    }
+
+    /// Reports the change of the scroll valuators in the motion `event` as scrolling.
+    fn scroll_event_linux_x(
+        &mut self,
+        event: &xinput::MotionEvent,
+        event_closure: &mut impl FnMut(WindowEvent),
+    ) -> Result<(), WindowError> {
+        let valuators = match self.scroll_valuators.entry(event.source().id()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Self::query_scroll_valuators(&self.connection, event.source())?),
+        };
+
+        let (mut delta_x, mut delta_y) = (0.0, 0.0);
+
+        for (number, value) in utility::valuator_values(event.valuator_mask(), event.axisvalues()) {
+            let Some(valuator) = valuators.iter_mut().find(|valuator| valuator.number == number) else { continue };
+
+            if let Some(previous_value) = valuator.value.replace(value) {
+                let delta = (value - previous_value) / valuator.increment;
+
+                // The vertical valuators grow when scrolling down.
+                if valuator.horizontal {
+                    delta_x += delta;
+                } else {
+                    delta_y -= delta;
+                }
+            }
+        }
+
+        if delta_x != 0.0 || delta_y != 0.0 {
+            (event_closure)(WindowEvent::Input(WindowInputEvent::MouseWheel {
+                delta_x, delta_y, unit: ScrollUnit::Lines
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the scroll valuators of the XInput 2 `device`.
+    fn query_scroll_valuators(
+        conn: &xcb::Connection,
+        device: xinput::Device,
+    ) -> Result<Vec<ScrollValuator>, WindowError> {
+        // The device may have been removed in the meantime.
+        let reply = match conn.wait_for_reply(conn.send_request(&xinput::XiQueryDevice { device })) {
+            Ok(reply) => reply,
+            Err(xcb::Error::Protocol(xcb::ProtocolError::Input(xinput::Error::Device(_), _))) => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let classes: Vec<_> = reply.infos().flat_map(|info| info.classes()).map(|class| class.data()).collect();
+
+        // The current values are the baseline of the first scroll, which would otherwise only set it.
+        let current_value = |number: u16| classes.iter().find_map(|data| match *data {
+            xinput::DeviceClassData::Valuator { number: valuator, value, .. } if valuator == number => {
+                Some(utility::fp3232_to_f64(value))
+            },
+            _ => None,
+        });
+
+        let valuators = classes.iter()
+            .filter_map(|data| match *data {
+                xinput::DeviceClassData::Scroll { number, scroll_type, increment, .. } => {
+                    let increment = utility::fp3232_to_f64(increment);
+
+                    (increment != 0.0).then_some(ScrollValuator {
+                        number,
+                        horizontal: scroll_type == xinput::ScrollType::Horizontal,
+                        increment,
+                        value: current_value(number),
+                    })
+                },
+                _ => None,
+            })
+            .collect();
+
+        Ok(valuators)
+    }
+
+    /// Returns whether the property change `event` is one of the settings the DPI and double click are read from.
+    fn is_settings_change(&self, event: &x::PropertyNotifyEvent) -> bool {
+        (event.window() == self.root_linux_x() && event.atom() == x::ATOM_RESOURCE_MANAGER)
+            || (event.window() == self.xsettings_owner && event.atom() == self.atoms.xsettings_settings)
+    }
+
+    /// Returns the window of the XSETTINGS manager, watching it for changes of the settings, or `x::Window::none()`
+    /// if there is no manager.
+    fn watch_xsettings_linux_x(&self) -> Result<x::Window, WindowError> {
+        let owner = self.connection.wait_for_reply(self.connection.send_request(&x::GetSelectionOwner {
+            selection: self.xsettings_selection,
+        }))?.owner();
+
+        if owner.is_none() {
+            return Ok(owner);
+        }
+
+        let cookie = self.connection.send_request_checked(&x::ChangeWindowAttributes {
+            window: owner,
+            value_list: &[x::Cw::EventMask(x::EventMask::PROPERTY_CHANGE | x::EventMask::STRUCTURE_NOTIFY)],
+        });
+
+        // The manager may have exited in the meantime.
+        match self.connection.check_request(cookie) {
+            Ok(()) => Ok(owner),
+            Err(xcb::ProtocolError::X(x::Error::Window(_), _)) => Ok(x::Window::none()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the contents of the `_XSETTINGS_SETTINGS` property, or nothing without an XSETTINGS manager.
+    fn read_xsettings_linux_x(&self) -> Vec<u8> {
+        if self.xsettings_owner.is_none() {
+            return Vec::new();
+        }
+
+        let cookie = self.connection.send_request(&x::GetProperty {
+            delete: false,
+            window: self.xsettings_owner,
+            property: self.atoms.xsettings_settings,
+            r#type: self.atoms.xsettings_settings,
+            long_offset: 0,
+            long_length: u32::MAX / 4,
+        });
+
+        // A manager that exited has no settings.
+        self.connection.wait_for_reply(cookie).map(|reply| reply.value().to_vec()).unwrap_or_default()
+    }
+
+    /// Reads the DPI the desktop is set to, from the XSETTINGS or else from the X resources.
+    fn read_xft_dpi_linux_x(&self) -> Result<Option<f64>, WindowError> {
+        let xsettings = self.read_xsettings_linux_x();
+
+        // XSETTINGS holds the DPI multiplied by 1024.
+        if let Some(dpi) = utility::xsettings_integer(&xsettings, "Xft/DPI").filter(|&dpi| dpi > 0) {
+            return Ok(Some(dpi as f64 / 1024.0));
+        }
+
+        let reply = self.connection.wait_for_reply(self.connection.send_request(&x::GetProperty {
+            delete: false,
+            window: self.root_linux_x(),
+            property: x::ATOM_RESOURCE_MANAGER,
+            r#type: x::ATOM_STRING,
+            long_offset: 0,
+            long_length: u32::MAX / 4,
+        }))?;
+
+        let resources = String::from_utf8_lossy(reply.value());
+
+        Ok(utility::xresource(&resources, "Xft.dpi").and_then(|dpi| dpi.parse().ok()).filter(|&dpi: &f64| dpi > 0.0))
+    }
+
+    /// Reads the double click time and distance the desktop is set to from the XSETTINGS, defaulting to those of GTK.
+    fn read_double_click_linux_x(&self) -> (u32, i32) {
+        let xsettings = self.read_xsettings_linux_x();
+
+        let time = utility::xsettings_integer(&xsettings, "Net/DoubleClickTime").filter(|&time| time > 0);
+        let distance = utility::xsettings_integer(&xsettings, "Net/DoubleClickDistance")
+            .filter(|&distance| distance >= 0);
+
+        (time.map_or(400, |time| time as u32), distance.unwrap_or(5))
+    }
+
+    /// Rereads the settings of the desktop after a change, and reports a change of the scale factor.
+    fn update_settings_linux_x(&mut self, event_closure: impl FnMut(WindowEvent)) -> Result<(), WindowError> {
+        self.double_click = self.read_double_click_linux_x();
+        self.update_scale_factor_linux_x(event_closure)
+    }
+
+    /// Rereads the DPI the desktop is set to, and reports a change of the scale factor.
+    fn update_scale_factor_linux_x(&mut self, mut event_closure: impl FnMut(WindowEvent)) -> Result<(), WindowError> {
+        let previous_scale = self.scale_factor();
+        self.xft_dpi = self.read_xft_dpi_linux_x()?;
+        let scale = self.scale_factor();
+
+        if scale != previous_scale {
+            let suggested_size = self.inner_size()?.to_logical(previous_scale).to_physical(scale);
+
+            (event_closure)(WindowEvent::ScaleFactorChanged { scale, suggested_size });
+        }
+
+        Ok(())
+    }
+
+    /// Tells apart real focus changes from the temporary ones of keyboard grabs, such as those of the window manager
+    /// while switching windows, and from the changes of the pointer root focus.
+    fn is_focus_change(mode: x::NotifyMode, detail: x::NotifyDetail) -> bool {
+        matches!(mode, x::NotifyMode::Normal | x::NotifyMode::WhileGrabbed)
+            && !matches!(detail, x::NotifyDetail::Pointer | x::NotifyDetail::Inferior)
+    }
+
+    /// Tells apart the cursor crossing the border of the window from crossings into its children, and from the
+    /// crossings pointer grabs and ungrabs report without the cursor moving.
+    fn is_crossing(mode: x::NotifyMode, detail: x::NotifyDetail) -> bool {
+        mode == x::NotifyMode::Normal && detail != x::NotifyDetail::Inferior
+    }
+
+    /// Returns whether `event` is a crossing, motion or button event of the pointer.
+    fn is_pointer_event(event: &xcb::Event) -> bool {
+        matches!(
+            event,
+            xcb::Event::X(x::Event::EnterNotify(_) | x::Event::MotionNotify(_) | x::Event::ButtonPress(_)
+                | x::Event::ButtonRelease(_))
+                | xcb::Event::Input(xinput::Event::Motion(_) | xinput::Event::ButtonPress(_)
+                    | xinput::Event::ButtonRelease(_))
+        )
+    }
+
+    fn has_focus_linux_x(&self) -> bool {
+        let cookie = self.connection.send_request(&x::GetInputFocus {});
+
+        match self.connection.wait_for_reply(cookie) {
+            Ok(reply) => reply.focus() == self.window,
+            Err(e) => {
+                log::warn!("Failed to query the input focus: {}", e);
+                false
+            },
+        }
+    }
+
+    /// Returns the `WM_NORMAL_HINTS` to set for the size constraints `size_hints`. A window that is not resizable has
+    /// its minimum and maximum size fixed at `inner_size`.
+    fn effective_size_hints(
+        size_hints: utility::WmSizeHints,
+        resizable: bool,
+        inner_size: (u32, u32),
+        position: Option<(i32, i32)>,
+    ) -> utility::WmSizeHints {
+        let mut size_hints = utility::WmSizeHints { position, ..size_hints };
+
+        if !resizable {
+            size_hints.min_size = Some(inner_size);
+            size_hints.max_size = Some(inner_size);
+        }
+
+        size_hints
+    }
+
+    /// Updates the `WM_NORMAL_HINTS` of the window to the current size constraints. `inner_size` is the size a window
+    /// that is not resizable is fixed at, or `None` to keep the current size.
+    fn update_size_hints_linux_x(&self, inner_size: Option<(u32, u32)>) -> Result<(), WindowError> {
+        let mut size_hints = self.size_hints;
+
+        if !self.resizable {
+            let inner_size = match inner_size {
+                Some(size) => size,
+                None => self.inner_size_linux_x()?,
+            };
+
+            size_hints = Self::effective_size_hints(size_hints, false, inner_size, None);
+        }
+
+        self.connection.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window: self.window,
+            property: x::ATOM_WM_NORMAL_HINTS,
+            r#type: x::ATOM_WM_SIZE_HINTS,
+            data: &size_hints.to_wire(),
+        });
+
+        Ok(self.connection.flush()?)
+    }
+
+    fn inner_size_linux_x(&self) -> Result<(u32, u32), WindowError> {
+        let cookie = self.connection.send_request(&x::GetGeometry {
+            drawable: x::Drawable::Window(self.window),
+        });
+        let reply = self.connection.wait_for_reply(cookie)?;
+
+        Ok((reply.width() as u32, reply.height() as u32))
+    }
+
+    fn set_inner_size_linux_x(&self, width: u32, height: u32) -> Result<(), WindowError> {
+        self.update_size_hints_linux_x(Some((width, height)))?;
+
+        self.connection.send_request(&x::ConfigureWindow {
+            window: self.window,
+            value_list: &[x::ConfigWindow::Width(width), x::ConfigWindow::Height(height)],
+        });
+
+        Ok(self.connection.flush()?)
+    }
+
+    fn apply_size_hints_linux_x(&self) {
+        if let Err(e) = self.update_size_hints_linux_x(None) {
+            log::warn!("Failed to apply the window size constraints: {}", e);
+        }
+    }
+
+    fn root_linux_x(&self) -> x::Window {
+        self.connection.get_setup().roots().nth(self.screen as usize).unwrap().root()
+    }
+
+    fn inner_position_linux_x(&self) -> Result<(i32, i32), WindowError> {
+        let cookie = self.connection.send_request(&x::TranslateCoordinates {
+            src_window: self.window,
+            dst_window: self.root_linux_x(),
+            src_x: 0,
+            src_y: 0,
+        });
+        let reply = self.connection.wait_for_reply(cookie)?;
+
+        Ok((reply.dst_x() as i32, reply.dst_y() as i32))
+    }
+
+    fn outer_position_linux_x(&self) -> Result<(i32, i32), WindowError> {
+        let (x, y) = self.inner_position_linux_x()?;
+        let (left, top) = self.frame_extents_linux_x()?;
+
+        Ok((x - left, y - top))
+    }
+
+    /// Returns the width of the left and top window manager decorations, or zero if the window manager does not
+    /// report them.
+    fn frame_extents_linux_x(&self) -> Result<(i32, i32), WindowError> {
+        let cookie = self.connection.send_request(&x::GetProperty {
+            delete: false,
+            window: self.window,
+            property: self.atoms.net_frame_extents,
+            r#type: x::ATOM_CARDINAL,
+            long_offset: 0,
+            long_length: 4,
+        });
+        let reply = self.connection.wait_for_reply(cookie)?;
+
+        // _NET_FRAME_EXTENTS holds the left, right, top and bottom extents.
+        match reply.value::<u32>() {
+            &[left, _, top, _] => Ok((left as i32, top as i32)),
+            _ => Ok((0, 0)),
+        }
+    }
+
+    /// Moves the window. With the default north west gravity, the window manager places the top left corner of the
+    /// frame at the requested position.
+    fn set_outer_position_linux_x(&self, x: i32, y: i32) {
+        self.connection.send_request(&x::ConfigureWindow {
+            window: self.window,
+            value_list: &[x::ConfigWindow::X(x), x::ConfigWindow::Y(y)],
+        });
+
+        if let Err(e) = self.connection.flush() {
+            log::warn!("Failed to move the window: {}", e);
+        }
+    }
+
+    /// Sends a client message about the window to the window manager, which selects the substructure redirect of the
+    /// root window.
+    fn send_wm_message_linux_x(&self, message_type: x::Atom, data: [u32; 5]) -> Result<(), WindowError> {
+        let event = x::ClientMessageEvent::new(self.window, message_type, x::ClientMessageData::Data32(data));
+
+        self.connection.send_request(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(self.root_linux_x()),
+            event_mask: x::EventMask::SUBSTRUCTURE_NOTIFY | x::EventMask::SUBSTRUCTURE_REDIRECT,
+            event: &event,
+        });
+
+        Ok(self.connection.flush()?)
+    }
+
+    /// Asks the window manager to activate the window.
+    fn focus_linux_x(&self) {
+        // Source indication 1 marks the request as coming from a normal application.
+        let data = [1, x::CURRENT_TIME, 0, 0, 0];
+
+        if let Err(e) = self.send_wm_message_linux_x(self.atoms.net_active_window, data) {
+            log::warn!("Failed to request focus: {}", e);
+        }
+    }
+
+    /// Asks the window manager to add or remove the states `first` and `second` of the window. `second` may be
+    /// `x::ATOM_NONE` to change a single state.
+    fn change_net_wm_state_linux_x(&self, add: bool, first: x::Atom, second: x::Atom) -> Result<(), WindowError> {
+        // Source indication 1 marks the request as coming from a normal application.
+        let data = [add as u32, first.resource_id(), second.resource_id(), 1, 0];
+
+        self.send_wm_message_linux_x(self.atoms.net_wm_state, data)
+    }
+
+    /// Writes `title` as the `WM_NAME` of `window`, for window managers without EWMH support, and as the UTF-8
+    /// `_NET_WM_NAME`.
+    fn write_title_linux_x(conn: &xcb::Connection, window: x::Window, atoms: &XAtoms, title: &str) {
+        // WM_NAME is a STRING, which is Latin-1.
+        let latin1: Vec<u8> = title.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect();
+
+        conn.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
             window,
-            property: wm_protocols,
-            r#type: x::ATOM_ATOM,
-            data: &[wm_del_window],
-        })).unwrap();
+            property: x::ATOM_WM_NAME,
+            r#type: x::ATOM_STRING,
+            data: &latin1,
+        });
+
+        conn.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window,
+            property: atoms.net_wm_name,
+            r#type: atoms.utf8_string,
+            data: title.as_bytes(),
+        });
+    }
+
+    fn set_title_linux_x(&self, title: &str) {
+        Self::write_title_linux_x(&self.connection, self.window, &self.atoms, title);
+
+        if let Err(e) = self.connection.flush() {
+            log::warn!("Failed to set the window title: {}", e);
+        }
+    }
+
+    fn set_visible_linux_x(&self, visible: bool) {
+        if visible {
+            self.connection.send_request(&x::MapWindow { window: self.window });
+        } else {
+            self.connection.send_request(&x::UnmapWindow { window: self.window });
+        }
+
+        if let Err(e) = self.connection.flush() {
+            log::warn!("Failed to change the window visibility: {}", e);
+        }
+    }
+
+    fn minimize_linux_x(&self) {
+        // Asks for the IconicState of the ICCCM.
+        if let Err(e) = self.send_wm_message_linux_x(self.atoms.wm_change_state, [3, 0, 0, 0, 0]) {
+            log::warn!("Failed to minimize the window: {}", e);
+        }
+    }
+
+    fn set_maximized_linux_x(&self, maximized: bool) {
+        let (vert, horz) = (self.atoms.net_wm_state_maximized_vert, self.atoms.net_wm_state_maximized_horz);
+
+        if let Err(e) = self.change_net_wm_state_linux_x(maximized, vert, horz) {
+            log::warn!("Failed to change the maximized state of the window: {}", e);
+        }
+    }
+
+    fn restore_linux_x(&self) {
+        self.set_maximized_linux_x(false);
+
+        // Mapping an iconified window makes the window manager show it again. A hidden window stays hidden.
+        if self.visible {
+            self.set_visible_linux_x(true);
+        }
+    }
+
+    fn randr_linux_x(&self, version: (u32, u32), feature: &'static str) -> Result<(), WindowError> {
+        if self.randr_version >= version {
+            Ok(())
+        } else {
+            Err(WindowError::Unsupported(feature))
+        }
+    }
+
+    /// Returns the monitors RandR reports, which are the active outputs unless configured otherwise.
+    fn monitors_linux_x(&self) -> Result<Vec<Monitor>, WindowError> {
+        self.randr_linux_x((1, 2), "RandR 1.2")?;
+
+        let root = self.root_linux_x();
+        let resources = ScreenResources::query(&self.connection, root, self.randr_version)?;
+
+        // Monitors arrived with RandR 1.5. Before that, each output shown by a CRTC is a monitor of its own.
+        if self.randr_version < (1, 5) {
+            return self.output_monitors_linux_x(&resources);
+        }
+
+        let monitors_reply = self.connection.wait_for_reply(self.connection.send_request(
+            &randr::GetMonitors { window: root, get_active: true }
+        ))?;
+
+        let mut monitors = Vec::new();
+
+        for monitor_info in monitors_reply.monitors() {
+            let name = self.connection.wait_for_reply(self.connection.send_request(
+                &x::GetAtomName { atom: monitor_info.name() }
+            ))?.name().to_utf8().into_owned();
+
+            let mut refresh_rate_millihertz = None;
+            let mut video_modes = Vec::new();
+
+            // A monitor made of several outputs, such as a tiled display, switches video modes through the first
+            // one. Monitors set up by the user may have no output at all.
+            if let Some(&output) = monitor_info.outputs().first() {
+                let output_info = self.connection.wait_for_reply(self.connection.send_request(
+                    &randr::GetOutputInfo { output, config_timestamp: resources.config_timestamp }
+                ))?;
+                let crtc = output_info.crtc();
+
+                if !crtc.is_none() {
+                    let crtc_info = self.connection.wait_for_reply(self.connection.send_request(
+                        &randr::GetCrtcInfo { crtc, config_timestamp: resources.config_timestamp }
+                    ))?;
+
+                    (refresh_rate_millihertz, video_modes) =
+                        self.video_modes_linux_x(&resources, &output_info, &crtc_info);
+                }
+            }
+
+            monitors.push(Monitor {
+                name,
+                position: (monitor_info.x() as i32, monitor_info.y() as i32),
+                size: (monitor_info.width() as u32, monitor_info.height() as u32),
+                size_mm: (monitor_info.width_in_millimeters(), monitor_info.height_in_millimeters()),
+                refresh_rate_millihertz,
+                scale_factor: self.scale_factor_linux_x(
+                    monitor_info.width() as u32, monitor_info.width_in_millimeters()
+                ),
+                primary: monitor_info.primary(),
+                video_modes,
+            });
+        }
+
+        Ok(monitors)
+    }
+
+    /// Returns a monitor for each output a CRTC shows, for servers without RandR 1.5.
+    fn output_monitors_linux_x(&self, resources: &ScreenResources) -> Result<Vec<Monitor>, WindowError> {
+        // The primary output arrived with RandR 1.3.
+        let primary_output = if self.randr_version >= (1, 3) {
+            self.connection.wait_for_reply(self.connection.send_request(
+                &randr::GetOutputPrimary { window: self.root_linux_x() }
+            ))?.output()
+        } else {
+            randr::Output::none()
+        };
+
+        let mut monitors = Vec::new();
+
+        for &output in &resources.outputs {
+            let output_info = self.connection.wait_for_reply(self.connection.send_request(
+                &randr::GetOutputInfo { output, config_timestamp: resources.config_timestamp }
+            ))?;
+            let crtc = output_info.crtc();
+
+            if output_info.connection() != randr::Connection::Connected || crtc.is_none() {
+                continue;
+            }
+
+            let crtc_info = self.connection.wait_for_reply(self.connection.send_request(
+                &randr::GetCrtcInfo { crtc, config_timestamp: resources.config_timestamp }
+            ))?;
+            let (refresh_rate_millihertz, video_modes) = self.video_modes_linux_x(resources, &output_info, &crtc_info);
+
+            monitors.push(Monitor {
+                name: String::from_utf8_lossy(output_info.name()).into_owned(),
+                position: (crtc_info.x() as i32, crtc_info.y() as i32),
+                size: (crtc_info.width() as u32, crtc_info.height() as u32),
+                size_mm: (output_info.mm_width(), output_info.mm_height()),
+                refresh_rate_millihertz,
+                scale_factor: self.scale_factor_linux_x(crtc_info.width() as u32, output_info.mm_width()),
+                primary: output == primary_output,
+                video_modes,
+            });
+        }
+
+        Ok(monitors)
+    }
+
+    /// Returns the refresh rate of the video mode the CRTC shows the output in, and the video modes of the output.
+    fn video_modes_linux_x(
+        &self,
+        resources: &ScreenResources,
+        output_info: &randr::GetOutputInfoReply,
+        crtc_info: &randr::GetCrtcInfoReply,
+    ) -> (Option<u32>, Vec<VideoMode>) {
+        let bit_depth = self.connection.get_setup().roots().nth(self.screen as usize).unwrap().root_depth() as u16;
+        let crtc = output_info.crtc();
+
+        let refresh_rate_millihertz = resources.mode_info(crtc_info.mode())
+            .map(utility::refresh_rate_millihertz)
+            .filter(|&refresh_rate| refresh_rate > 0);
+
+        let video_modes = output_info.modes().iter().filter_map(|&mode| {
+            let mode_info = resources.mode_info(mode)?;
+
+            Some(VideoMode {
+                size: (mode_info.width as u32, mode_info.height as u32),
+                bit_depth,
+                refresh_rate_millihertz: utility::refresh_rate_millihertz(mode_info),
+                mode,
+                crtc,
+            })
+        }).collect();
+
+        (refresh_rate_millihertz, video_modes)
+    }
+
+    /// Returns the scale factor of a monitor, from `Xft.dpi` if set or else from the pixel density of the monitor.
+    fn scale_factor_linux_x(&self, width: u32, width_mm: u32) -> f64 {
+        self.xft_dpi.map_or_else(|| utility::scale_factor_from_density(width, width_mm), |dpi| dpi / 96.0)
+    }
+
+    /// Returns the monitor showing the largest part of the window.
+    fn current_monitor_linux_x(&self) -> Result<Option<Monitor>, WindowError> {
+        let (x, y) = self.inner_position_linux_x()?;
+        let (width, height) = self.inner_size_linux_x()?;
+
+        let overlap = |monitor: &Monitor| {
+            let (monitor_x, monitor_y) = monitor.position;
+            let (monitor_width, monitor_height) = monitor.size;
+
+            let width = (x + width as i32).min(monitor_x + monitor_width as i32) - x.max(monitor_x);
+            let height = (y + height as i32).min(monitor_y + monitor_height as i32) - y.max(monitor_y);
+
+            width.max(0) as i64 * height.max(0) as i64
+        };
+
+        let mut monitors = self.monitors_linux_x()?;
+        let index = (0..monitors.len()).rev().max_by_key(|&i| overlap(&monitors[i]));
+
+        Ok(index.map(|i| monitors.swap_remove(i)))
+    }
+
+    fn set_fullscreen_linux_x(&mut self, fullscreen: Option<&Fullscreen>) -> Result<(), WindowError> {
+        let video_mode = match fullscreen {
+            Some(Fullscreen::Exclusive(video_mode)) => Some(video_mode),
+            _ => None,
+        };
+
+        // A switch to another monitor leaves the switched one in its original video mode.
+        if let Some(saved_crtc) = &self.saved_crtc {
+            if video_mode.map(|video_mode| video_mode.crtc) != Some(saved_crtc.crtc) {
+                self.restore_video_mode_linux_x()?;
+            }
+        }
+
+        let position = match fullscreen {
+            Some(Fullscreen::Borderless(Some(monitor))) => Some(monitor.position),
+            Some(Fullscreen::Exclusive(video_mode)) => Some(self.switch_video_mode_linux_x(video_mode)?),
+            _ => None,
+        };
+
+        let fullscreen_state = self.atoms.net_wm_state_fullscreen;
+
+        // The window manager makes a fullscreen window cover the monitor it is on, but keeps the window in place while
+        // it is fullscreen already.
+        if let Some((x, y)) = position {
+            if self.fullscreen.is_some() {
+                self.change_net_wm_state_linux_x(false, fullscreen_state, x::ATOM_NONE)?;
+            }
+
+            self.set_outer_position_linux_x(x, y);
+        }
+
+        self.change_net_wm_state_linux_x(fullscreen.is_some(), fullscreen_state, x::ATOM_NONE)
+    }
+
+    /// Switches the CRTC of `video_mode` to it, saving the configuration to restore, and returns the position of
+    /// the CRTC.
+    fn switch_video_mode_linux_x(&mut self, video_mode: &VideoMode) -> Result<(i32, i32), WindowError> {
+        self.randr_linux_x((1, 2), "RandR 1.2")?;
+
+        let config_timestamp = ScreenResources::query(&self.connection, self.window, self.randr_version)?
+            .config_timestamp;
+
+        let crtc_info = self.connection.wait_for_reply(self.connection.send_request(
+            &randr::GetCrtcInfo { crtc: video_mode.crtc, config_timestamp }
+        ))?;
+
+        let reply = self.connection.wait_for_reply(self.connection.send_request(&randr::SetCrtcConfig {
+            crtc: video_mode.crtc,
+            timestamp: x::CURRENT_TIME,
+            config_timestamp,
+            x: crtc_info.x(),
+            y: crtc_info.y(),
+            mode: video_mode.mode,
+            rotation: crtc_info.rotation(),
+            outputs: crtc_info.outputs(),
+        }))?;
+        Self::check_set_config(reply.status())?;
+
+        // Switching between video modes of the same CRTC keeps the configuration from before the first switch.
+        if self.saved_crtc.is_none() {
+            let saved_crtc = SavedCrtc {
+                root: self.root_linux_x(),
+                crtc: video_mode.crtc,
+                mode: crtc_info.mode(),
+                x: crtc_info.x(),
+                y: crtc_info.y(),
+                rotation: crtc_info.rotation(),
+                outputs: crtc_info.outputs().to_vec(),
+                randr_version: self.randr_version,
+            };
+
+            SavedCrtc::register(saved_crtc.clone());
+            self.saved_crtc = Some(saved_crtc);
+        }
+
+        Ok((crtc_info.x() as i32, crtc_info.y() as i32))
+    }
+
+    /// Switches the CRTC changed by exclusive fullscreen back to its original video mode.
+    fn restore_video_mode_linux_x(&mut self) -> Result<(), WindowError> {
+        let Some(saved_crtc) = self.saved_crtc.take() else { return Ok(()) };
+
+        SavedCrtc::unregister(saved_crtc.crtc);
+
+        saved_crtc.restore(&self.connection)
+    }
+
+    fn check_set_config(status: randr::SetConfig) -> Result<(), WindowError> {
+        match status {
+            randr::SetConfig::Success => Ok(()),
+            status => Err(WindowError::Protocol(format!("RandR SetCrtcConfig failed: {:?}", status))),
+        }
+    }
+
+    /// Returns the event held back while looking ahead, or else the next event from the connection.
+    fn next_event_linux_x(&mut self) -> Result<Option<xcb::Event>, WindowError> {
+        match self.pending_event.take() {
+            Some(event) => Ok(Some(event)),
+            None => Ok(self.connection.poll_for_event()?),
+        }
+    }
+
+    /// X key codes are the evdev key codes offset by 8.
+    fn translate_scancode(x_keycode: x::Keycode) -> ScanCode {
+        ScanCode(x_keycode as u32 - 8)
+    }
+
+    fn key_for_scancode_linux_x(&self, scancode: ScanCode) -> Option<Keys> {
+        let x_keycode = scancode.0.checked_add(8)?.try_into().ok()?;
+
+        self.translate_key_code(x_keycode, self.key_but_mask_linux_x())
+    }
+
+    fn cursor_position_linux_x(&self) -> Option<(i16, i16)> {
+        let cookie = self.connection.send_request(&x::QueryPointer {
+            window: self.window,
+        });
+
+        match self.connection.wait_for_reply(cookie) {
+            Ok(reply) if reply.same_screen() => Some((reply.win_x(), reply.win_y())),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("Failed to query the cursor position: {}", e);
+                None
+            },
+        }
+    }
+
+    /// Grabs the pointer for the grab mode of the window while it has focus, and releases it otherwise.
+    fn update_cursor_grab_linux_x(&mut self) -> Result<(), WindowError> {
+        self.locked_cursor_position = None;
+
+        if self.cursor_grab == CursorGrabMode::None || !self.focused {
+            // Ungrabbing without a grab of its own would end the grab the server takes for a button press.
+            if std::mem::take(&mut self.pointer_grabbed) {
+                self.connection.send_request(&x::UngrabPointer { time: x::CURRENT_TIME });
+                self.connection.flush()?;
+            }
+
+            return Ok(());
+        }
+
+        // The events keep going to the window as usual, the grab only confines the pointer.
+        let reply = self.connection.wait_for_reply(self.connection.send_request(&x::GrabPointer {
+            owner_events: true,
+            grab_window: self.window,
+            event_mask: x::EventMask::BUTTON_PRESS | x::EventMask::BUTTON_RELEASE | x::EventMask::POINTER_MOTION
+                | x::EventMask::ENTER_WINDOW | x::EventMask::LEAVE_WINDOW,
+            pointer_mode: x::GrabMode::Async,
+            keyboard_mode: x::GrabMode::Async,
+            confine_to: self.window,
+            cursor: x::Cursor::none(),
+            time: x::CURRENT_TIME,
+        }))?;
+
+        match reply.status() {
+            x::GrabStatus::Success => self.pointer_grabbed = true,
+            // The window manager may still hold the grab of the click that focused the window. The grab is retried
+            // on the next crossing, motion or button event.
+            x::GrabStatus::AlreadyGrabbed => return Ok(()),
+            status => return Err(WindowError::Protocol(format!("GrabPointer failed: {:?}", status))),
+        }
+
+        if self.cursor_grab == CursorGrabMode::Locked {
+            self.locked_cursor_position = self.cursor_position_linux_x();
+        }
+
+        Ok(())
+    }
+
+    /// Sets the cursor of the window to its cursor icon, or to an invisible cursor while the cursor is hidden.
+    fn update_cursor_linux_x(&self) -> Result<(), WindowError> {
+        let cursor = if self.cursor_visible {
+            match &self.cursor_context {
+                Some(context) => context.load(&self.cursor_icon.xcursor_names())
+                    .ok_or(WindowError::Protocol(format!("No cursor found for {:?}", self.cursor_icon)))?,
+                None => self.create_font_cursor_linux_x()?,
+            }
+        } else {
+            self.create_invisible_cursor_linux_x()?
+        };
+
+        let cookie = self.connection.send_request_checked(&x::ChangeWindowAttributes {
+            window: self.window,
+            value_list: &[x::Cw::Cursor(cursor)],
+        });
+
+        // The window keeps the cursor alive for as long as it uses it.
+        self.connection.send_request(&x::FreeCursor { cursor });
+        self.connection.check_request(cookie)?;
+
+        Ok(())
+    }
+
+    /// Creates the cursor of the cursor icon from the X core cursor font, in black on white.
+    fn create_font_cursor_linux_x(&self) -> Result<x::Cursor, WindowError> {
+        let font = self.connection.generate_id();
+        let cursor = self.connection.generate_id();
+        let glyph = self.cursor_icon.cursor_font_glyph();
+
+        self.connection.send_request(&x::OpenFont { fid: font, name: b"cursor" });
+
+        let cookie = self.connection.send_request_checked(&x::CreateGlyphCursor {
+            cid: cursor,
+            source_font: font,
+            mask_font: font,
+            source_char: glyph,
+            mask_char: glyph + 1,
+            fore_red: 0,
+            fore_green: 0,
+            fore_blue: 0,
+            back_red: 0xFFFF,
+            back_green: 0xFFFF,
+            back_blue: 0xFFFF,
+        });
+
+        // The cursor keeps the glyphs it was made from.
+        self.connection.send_request(&x::CloseFont { font });
+        self.connection.check_request(cookie)?;
+
+        Ok(cursor)
+    }
+
+    /// Creates a cursor from an empty 1x1 bitmap. The core protocol has no other way to hide the cursor.
+    fn create_invisible_cursor_linux_x(&self) -> Result<x::Cursor, WindowError> {
+        let pixmap = self.connection.generate_id();
+        let gc = self.connection.generate_id();
+        let cursor = self.connection.generate_id();
+
+        self.connection.send_request(&x::CreatePixmap {
+            depth: 1,
+            pid: pixmap,
+            drawable: x::Drawable::Window(self.window),
+            width: 1,
+            height: 1,
+        });
+
+        // The contents of a new pixmap are undefined, so the bitmap is cleared first.
+        self.connection.send_request(&x::CreateGc {
+            cid: gc,
+            drawable: x::Drawable::Pixmap(pixmap),
+            value_list: &[x::Gc::Foreground(0)],
+        });
+
+        self.connection.send_request(&x::PolyFillRectangle {
+            drawable: x::Drawable::Pixmap(pixmap),
+            gc,
+            rectangles: &[x::Rectangle { x: 0, y: 0, width: 1, height: 1 }],
+        });
+
+        let cookie = self.connection.send_request_checked(&x::CreateCursor {
+            cid: cursor,
+            source: pixmap,
+            mask: pixmap,
+            fore_red: 0,
+            fore_green: 0,
+            fore_blue: 0,
+            back_red: 0,
+            back_green: 0,
+            back_blue: 0,
+            x: 0,
+            y: 0,
+        });
+
+        self.connection.send_request(&x::FreeGc { gc });
+        self.connection.send_request(&x::FreePixmap { pixmap });
+        self.connection.check_request(cookie)?;
+
+        Ok(cursor)
+    }
+
+    /// Warps the cursor back if it moved away from `position` while locked. X11 has no way to stop the pointer, so
+    /// returns whether the cursor is locked, and the motion is not to be reported.
+    fn hold_locked_cursor_linux_x(&self, position: (i16, i16)) -> bool {
+        let Some((x, y)) = self.locked_cursor_position else { return false };
+
+        if position != (x, y) {
+            self.connection.send_request(&x::WarpPointer {
+                src_window: x::Window::none(),
+                dst_window: self.window,
+                src_x: 0,
+                src_y: 0,
+                src_width: 0,
+                src_height: 0,
+                dst_x: x,
+                dst_y: y,
+            });
+
+            if let Err(e) = self.connection.flush() {
+                log::warn!("Failed to move the cursor back: {}", e);
+            }
+        }
+
+        true
+    }
+
+    fn set_raw_input_linux_x(&mut self, enabled: bool) -> Result<(), WindowError> {
+        if !self.xinput2 {
+            return Err(WindowError::Unsupported("XInput 2.1"));
+        }
+
+        // The server only sends raw events to the root window.
+        let mask = if enabled { xinput::XiEventMask::RAW_MOTION } else { xinput::XiEventMask::empty() };
+
+        self.connection.check_request(self.connection.send_request_checked(&xinput::XiSelectEvents {
+            window: self.root_linux_x(),
+            masks: &[xinput::EventMaskBuf::new(xinput::Device::AllMaster, &[mask])],
+        }))?;
+
+        Ok(())
+    }
+
+    fn modifiers_linux_x(&self) -> Modifiers {
+        Self::translate_modifiers(self.key_but_mask_linux_x())
+    }
+
+    /// Returns whether a mouse button is held, which is how interactive resizes are told apart from the others. A
+    /// heuristic, see [`WindowEvent::Resize`].
+    fn pointer_button_held_linux_x(&self) -> bool {
+        self.key_but_mask_linux_x().intersects(
+            x::KeyButMask::BUTTON1 | x::KeyButMask::BUTTON2 | x::KeyButMask::BUTTON3 | x::KeyButMask::BUTTON4
+                | x::KeyButMask::BUTTON5,
+        )
+    }
+
+    /// Queries the current key and button mask, including the active keyboard group.
+    fn key_but_mask_linux_x(&self) -> x::KeyButMask {
+        let cookie = self.connection.send_request(&x::QueryPointer {
+            window: self.window,
+        });
+
+        match self.connection.wait_for_reply(cookie) {
+            Ok(reply) => reply.mask(),
+            Err(e) => {
+                log::warn!("Failed to query the modifier state: {}", e);
+                x::KeyButMask::empty()
+            },
+        }
+    }
+
+    /// Translates the modifiers of an XInput 2 event, which holds the same bits as the core state.
+    fn translate_xinput_modifiers(mods: xinput::ModifierInfo) -> Modifiers {
+        Self::translate_modifiers(x::KeyButMask::from_bits_truncate(mods.effective))
+    }
+
+    /// Translates an X key and button mask, assuming the common modifier mapping of Alt on Mod1, Num Lock on Mod2
+    /// and Super on Mod4.
+    fn translate_modifiers(state: x::KeyButMask) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
 
-        conn.flush().unwrap();
+        modifiers.set(Modifiers::SHIFT, state.contains(x::KeyButMask::SHIFT));
+        modifiers.set(Modifiers::CONTROL, state.contains(x::KeyButMask::CONTROL));
+        modifiers.set(Modifiers::ALT, state.contains(x::KeyButMask::MOD1));
+        modifiers.set(Modifiers::SUPER, state.contains(x::KeyButMask::MOD4));
+        modifiers.set(Modifiers::CAPS_LOCK, state.contains(x::KeyButMask::LOCK));
+        modifiers.set(Modifiers::NUM_LOCK, state.contains(x::KeyButMask::MOD2));
 
-        Self {
-            previous_size: (0, 0),
-            connection: conn,
-            screen: screen_num,
-            window: window.resource_id(),
-            wm_del_window,
+        modifiers
+    }
+
+    /// Returns the modifiers after the release of the key `x_keycode`, from the `state` before it. The modifier of the
+    /// key stays set while another key for it, such as the other Shift, is still held.
+    fn release_modifiers_linux_x(&self, x_keycode: x::Keycode, state: x::KeyButMask) -> Modifiers {
+        let released = self.key_modifier_linux_x(x_keycode, state);
+
+        let still_held = self.pressed_keys.iter()
+            .any(|&keycode| self.key_modifier_linux_x(keycode, state) == released);
+
+        let modifiers = Self::translate_modifiers(state);
+
+        if still_held { modifiers } else { modifiers - released }
+    }
+
+    /// Returns the modifier held down by the key `x_keycode`, if it is a modifier key. AltGr is reported as
+    /// [`Keys::RMenu`], but selects the third level of the layout instead of holding Alt, so that typing with it is
+    /// not taken for a shortcut.
+    fn key_modifier_linux_x(&self, x_keycode: x::Keycode, state: x::KeyButMask) -> Modifiers {
+        let key_sym = self.translate_key_sym(x_keycode, state);
+
+        if key_sym == x11::keysym::XK_ISO_Level3_Shift {
+            return Modifiers::empty();
+        }
+
+        match Keys::from_keysym(key_sym) {
+            Some(Keys::Shift | Keys::LShift | Keys::RShift) => Modifiers::SHIFT,
+            Some(Keys::Control | Keys::LControl | Keys::RControl) => Modifiers::CONTROL,
+            Some(Keys::LMenu | Keys::RMenu) => Modifiers::ALT,
+            Some(Keys::LWin | Keys::RWin) => Modifiers::SUPER,
+            _ => Modifiers::empty(),
         }
     }
 
-    fn poll_messages_linux_x(&mut self, mut event_closure: impl FnMut(WindowEvent)) {
-        while let Some(event) = self.connection.poll_for_event().unwrap() {
-            if let xcb::Event::X(event) = event { match event {
-                    x::Event::KeyPress(event) => {
-                        if let Some(key) = self.translate_key_code(event.detail()) {
-                            (event_closure)(WindowEvent::Input(WindowInputEvent::KeyDown(key)));
-                        }
-                    },
-                    x::Event::KeyRelease(event) => {
-                        if let Some(key) = self.translate_key_code(event.detail()) {
-                            (event_closure)(WindowEvent::Input(WindowInputEvent::KeyUp(key)));
-                        }
-                    },
-                    x::Event::ButtonPress(event) => {
-                        let button = match event.detail() as c_uint {
-                            x11::xlib::Button1 => MouseButton::Left,
-                            x11::xlib::Button2 => MouseButton::Middle,
-                            x11::xlib::Button3 => MouseButton::Right,
-                            x11::xlib::Button4 => continue,
-                            x11::xlib::Button5 => continue,
-                            _ => { log::warn!("Unrecognized mouse button pressed: {}.", event.detail()); continue; },
-                        };
+    fn raw_window_handle_linux_x(&self) -> RawWindowHandle {
+        let handle = XcbWindowHandle::new(NonZeroU32::new(self.window.resource_id()).unwrap());
 
-                        (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown(button)));
-                    },
-                    x::Event::ButtonRelease(event) => {
-                        match event.detail() as c_uint{
-                            event @ (x11::xlib::Button1 | x11::xlib::Button2 | x11::xlib::Button3) => {
-                                let button = match event {
-                                    x11::xlib::Button1 => MouseButton::Left,
-                                    x11::xlib::Button2 => MouseButton::Middle,
-                                    x11::xlib::Button3 => MouseButton::Right,
-                                    _ => panic!("Unrecognized mouse button x keycode.")
-                                };
-
-                                (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp(button)));
-                            },
-                            event @ (x11::xlib::Button4 | x11::xlib::Button5) => {
-                                let d = match event {
-                                    x11::xlib::Button4 => 1i16,
-                                    x11::xlib::Button5 => -1i16,
-                                    _ => panic!("Unrecognized mouse button x keycode.")
-                                };
-
-                                (event_closure)(WindowEvent::Input(WindowInputEvent::MouseWheelMove(d)));
-                            },
-                            _ => { log::warn!("Unrecognized mouse button released: {}.", event.detail()); continue; },
-                        };
-                    },
-                    x::Event::MotionNotify(event) => {
-                        let x = event.event_x();
-                        let y = event.event_x();
-                        
-                        (event_closure)(WindowEvent::Input(WindowInputEvent::MouseMove(x, y)));
-                    },
-                    x::Event::ConfigureNotify(event) => {
-                        // Window resize. Also triggered by window move.
+        RawWindowHandle::Xcb(handle)
+    }
 
-                        let x = event.width() as u32;
-                        let y = event.height() as u32;
+    fn raw_display_handle_linux_x(&self) -> RawDisplayHandle {
+        let handle = XcbDisplayHandle::new(
+            Some(NonNull::new(self.connection.get_raw_conn() as *mut c_void).unwrap()), self.screen
+        );
 
-                        if self.previous_size != (x, y) {
-                            self.previous_size = (x, y);
+        RawDisplayHandle::Xcb(handle)
+    }
 
-                            (event_closure)(WindowEvent::Resize(x, y));
-                        }
-                    },
-                    x::Event::ClientMessage(event) => {
-                        if let x::ClientMessageData::Data32([atom, ..]) = event.data() {
-                            if atom == self.wm_del_window.resource_id() {
-                                (event_closure)(WindowEvent::Close);
-                            }
-                        }
-                    },
-                    _ => {},
+    /// Returns the text the key press `event` produces, if any.
+    fn lookup_text(&self, event: &x::KeyPressEvent) -> Option<String> {
+        use x11::xlib::{XEvent, XFilterEvent, XKeyEvent, Xutf8LookupString, XBufferOverflow, XLookupBoth,
+            XLookupChars};
+
+        let ic = self.input_context.as_ref()?.ic;
+
+        let mut x_event = XEvent {
+            key: XKeyEvent {
+                type_: x11::xlib::KeyPress,
+                serial: 0,
+                send_event: 0,
+                display: self.connection.get_raw_dpy(),
+                window: self.window.resource_id() as c_ulong,
+                root: event.root().resource_id() as c_ulong,
+                subwindow: event.child().resource_id() as c_ulong,
+                time: event.time() as c_ulong,
+                x: event.event_x() as c_int,
+                y: event.event_y() as c_int,
+                x_root: event.root_x() as c_int,
+                y_root: event.root_y() as c_int,
+                state: event.state().bits() as c_uint,
+                keycode: event.detail() as c_uint,
+                same_screen: event.same_screen() as c_int,
+            },
+        };
+
+        // The input method consumes the key presses that are part of a compose or dead key sequence.
+        if unsafe { XFilterEvent(&mut x_event, 0) } != 0 {
+            return None;
+        }
+
+        let mut buffer = vec![0u8; 16];
+        let mut key_sym = 0;
+        let mut status = 0;
+
+        loop {
+            let count = unsafe {
+                Xutf8LookupString(
+                    ic, &mut x_event.key, buffer.as_mut_ptr() as *mut c_char, buffer.len() as c_int,
+                    &mut key_sym, &mut status
+                )
+            };
+
+            if status == XBufferOverflow {
+                buffer.resize(count as usize, 0);
+                continue;
+            }
+
+            buffer.truncate(count as usize);
+            break;
+        }
+
+        if status != XLookupChars && status != XLookupBoth {
+            return None;
+        }
+
+        let text: String = String::from_utf8(buffer).ok()?.chars().filter(|c| !c.is_control()).collect();
+
+        if text.is_empty() { None } else { Some(text) }
+    }
+
+    /// Translates a key code to a key by its symbol in the active group, ignoring shift levels so that a key keeps
+    /// its identity while Shift is held. Keypad keys use their Num Lock symbol when Num Lock is on. Keys without a
+    /// [`Keys`] value in the active group, such as the letters of a Cyrillic layout, use their symbol in the first
+    /// group, which keeps shortcuts working.
+    fn translate_key_code(&self, x_keycode: x::Keycode, state: x::KeyButMask) -> Option<Keys> {
+        let key_sym = self.translate_key_sym(x_keycode, state);
+        let key = Keys::from_keysym(key_sym);
+
+        if key.is_none() && key_sym != x11::xlib::NoSymbol as c_uint {
+            log::warn!("Unrecognized x keysym: {:#x}", key_sym);
+        }
+
+        key
+    }
+
+    /// Returns the symbol [`Window::translate_key_code`] translates the key code `x_keycode` by.
+    fn translate_key_sym(&self, x_keycode: x::Keycode, state: x::KeyButMask) -> c_uint {
+        let active_group = (state.bits() >> 13) & 0b11;
+
+        let keysym_at = |group: u32, level: c_uint| unsafe {
+            x11::xlib::XkbKeycodeToKeysym(
+                self.connection.get_raw_dpy(),
+                x_keycode as x11::xlib::KeyCode,
+                group as c_int,
+                level as c_int,
+            ) as c_uint
+        };
+
+        let keysym_in = |group: u32| {
+            let key_sym = keysym_at(group, 0);
+
+            if state.contains(x::KeyButMask::MOD2) {
+                let num_lock_sym = keysym_at(group, 1);
+
+                if (x11::keysym::XK_KP_Space..=x11::keysym::XK_KP_9).contains(&num_lock_sym) {
+                    return num_lock_sym;
                 }
             }
+
+            key_sym
+        };
+
+        let key_sym = keysym_in(active_group);
+
+        if active_group != 0 && Keys::from_keysym(key_sym).is_none() {
+            let first_group_sym = keysym_in(0);
+
+            if Keys::from_keysym(first_group_sym).is_some() {
+                return first_group_sym;
+            }
+        }
+
+        key_sym
+    }
+}
+
+
+#[cfg(target_os = "windows")]
+impl Window {
+    pub const WINDOW_CLASS_NAME: &'static str = "window_class";
+
+    fn new_win32(builder: &WindowBuilder) -> Result<Self, WindowError> {
+        let client_width: i32 = builder.inner_size.0.try_into().ok().filter(|&w| w > 0)
+            .ok_or_else(|| builder.invalid_geometry())?;
+        let client_height: i32 = builder.inner_size.1.try_into().ok().filter(|&h| h > 0)
+            .ok_or_else(|| builder.invalid_geometry())?;
+
+        let window_class_name_utf16 = Self::wide_null(
+            builder.window_class.as_deref().unwrap_or(Self::WINDOW_CLASS_NAME)
+        );
+        let application_name_utf16 = Self::wide_null(&builder.title);
+
+        let h_instance = unsafe { GetModuleHandleA(ptr::null()) };
+
+        Self::enable_dpi_awareness_win32();
+
+        let icon = unsafe { LoadIconW(h_instance, IDI_APPLICATION) };
+
+        let wc = WNDCLASSW {
+            // Clicks are counted by the window, double click messages would replace the second press.
+            style: 0,
+            lpfnWndProc: Some(win32_process_message),
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            hInstance: h_instance,
+            hIcon: icon,
+            hCursor: unsafe { LoadCursorW(0, IDC_ARROW) },
+            hbrBackground: 0,
+            lpszClassName: window_class_name_utf16.as_ptr(),
+            lpszMenuName: ptr::null(),
+        };
+
+        // Every window after the first one shares the already registered class.
+        if unsafe { RegisterClassW(&wc) } == 0 {
+            let error = unsafe { GetLastError() };
+
+            if error != ERROR_CLASS_ALREADY_EXISTS {
+                log::error!("Window registration failed.");
+                return Err(WindowError::Os(error));
+            }
+        }
+
+        let mut windowed_style = if builder.decorated {
+            WS_OVERLAPPED | WS_SYSMENU | WS_CAPTION | WS_MINIMIZEBOX
+        } else {
+            WS_POPUP
+        };
+
+        if builder.resizable && builder.decorated {
+            windowed_style |= WS_MAXIMIZEBOX | WS_THICKFRAME;
+        }
+
+        let window_style = if builder.fullscreen.is_some() { WS_POPUP } else { windowed_style };
+
+        let mut window_ex_style = WS_EX_APPWINDOW;
+
+        if builder.always_on_top {
+            window_ex_style |= WS_EX_TOPMOST;
+        }
+
+        let (mut window_x, mut window_y) = builder.position.unwrap_or((CW_USEDEFAULT, CW_USEDEFAULT));
+        let mut window_width = client_width;
+        let mut window_height = client_height;
+
+        let mut border_rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+        unsafe { AdjustWindowRectEx(&mut border_rect, windowed_style, 0, window_ex_style); }
+
+        if builder.position.is_some() {
+            window_x += border_rect.left;
+            window_y += border_rect.top;
+        }
+        window_width += border_rect.right - border_rect.left;
+        window_height += border_rect.bottom - border_rect.top;
+
+        // CW_USEDEFAULT only works for overlapped windows, so undecorated ones are centered on the primary monitor.
+        if builder.position.is_none() && !builder.decorated {
+            let rect = Self::win32_monitor_rect((0, 0));
+
+            window_x = rect.left + (rect.right - rect.left - window_width) / 2;
+            window_y = rect.top + (rect.bottom - rect.top - window_height) / 2;
+        }
+
+        let mut windowed = None;
+
+        if builder.fullscreen.is_some() {
+            // Cover the whole monitor the window would have been placed on, and leave fullscreen to the windowed
+            // position and size. Without a position, that is the top left corner of the monitor.
+            let rect = match &builder.fullscreen {
+                Some(Fullscreen::Borderless(Some(monitor))) => Self::win32_monitor_rect(monitor.position),
+                _ => Self::win32_monitor_rect(builder.position.unwrap_or((0, 0))),
+            };
+
+            if builder.position.is_none() {
+                window_x = rect.left;
+                window_y = rect.top;
+            }
+
+            windowed = Some((windowed_style, RECT {
+                left: window_x,
+                top: window_y,
+                right: window_x + window_width,
+                bottom: window_y + window_height,
+            }));
+
+            window_x = rect.left;
+            window_y = rect.top;
+            window_width = rect.right - rect.left;
+            window_height = rect.bottom - rect.top;
+        }
+
+        let handle = unsafe {
+            CreateWindowExW(
+                window_ex_style, window_class_name_utf16.as_ptr(), application_name_utf16.as_ptr(),
+                window_style, window_x, window_y, window_width, window_height,
+                0, 0, h_instance, ptr::null()
+            )
+        };
+
+        if handle == 0 {
+            let error = unsafe { GetLastError() };
+            log::error!("Window creation failed.");
+            return Err(WindowError::Os(error));
+        }
+
+        let state = Box::new(Win32WindowState {
+            min_inner_size: Cell::new(builder.min_inner_size),
+            max_inner_size: Cell::new(builder.max_inner_size),
+            inner_size: Cell::new((client_width as u32, client_height as u32)),
+            cursor: Cell::new(unsafe { LoadCursorW(0, IDC_ARROW) }),
+            ..Default::default()
+        });
+
+        unsafe { SetWindowLongPtrW(handle, GWLP_USERDATA, &*state as *const Win32WindowState as isize); }
+
+        if builder.transparent {
+            // An empty blur region makes DWM compose the window using the alpha channel, without blurring anything.
+            let region = unsafe { CreateRectRgn(0, 0, -1, -1) };
+
+            let blur_behind = DWM_BLURBEHIND {
+                dwFlags: DWM_BB_ENABLE | DWM_BB_BLURREGION,
+                fEnable: 1,
+                hRgnBlur: region,
+                fTransitionOnMaximized: 0,
+            };
+
+            if unsafe { DwmEnableBlurBehindWindow(handle, &blur_behind) } != 0 {
+                log::warn!("Failed to enable window transparency.");
+            }
+
+            unsafe { DeleteObject(region); }
+        }
+
+        // Show the window.
+        if builder.visible {
+            let show_window_command_flags = if builder.maximized { SW_MAXIMIZE } else { SW_SHOW };
+
+            unsafe { ShowWindow(handle, show_window_command_flags); }
+        }
+
+        let window = Self {
+            redraw_requested: false,
+            pending_damage: Vec::new(),
+            damage: Vec::new(),
+            fullscreen: builder.fullscreen.clone(),
+            click_counter: ClickCounter::default(),
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            cursor_icon: CursorIcon::Arrow,
+            h_instance,
+            hwnd: handle,
+            state,
+            high_surrogate: None,
+            tracking_mouse_leave: false,
+            windowed,
+            switched_display: None,
+        };
+
+        window.state.outer_position.set(window.outer_position_win32()?);
+
+        Ok(window)
+    }
+
+    /// Returns the outer size of the window `hwnd` with a client area of `inner_size`.
+    fn win32_outer_size(hwnd: HWND, inner_size: (u32, u32)) -> (i32, i32) {
+        let window_style = unsafe { GetWindowLongW(hwnd, GWL_STYLE) } as u32;
+        let window_ex_style = unsafe { GetWindowLongW(hwnd, GWL_EXSTYLE) } as u32;
+
+        let mut rect = RECT { left: 0, top: 0, right: inner_size.0 as i32, bottom: inner_size.1 as i32 };
+        unsafe { AdjustWindowRectEx(&mut rect, window_style, 0, window_ex_style); }
+
+        (rect.right - rect.left, rect.bottom - rect.top)
+    }
+
+    /// Adjusts the window rectangle `rect` the user is resizing by dragging `edge` to the resize increments and aspect
+    /// ratio of the window. The minimum and maximum size are applied by Windows afterwards.
+    fn win32_constrain_sizing(hwnd: HWND, state: &Win32WindowState, edge: u32, rect: &mut RECT) {
+        let (frame_width, frame_height) = Self::win32_outer_size(hwnd, (0, 0));
+        let mut width = (rect.right - rect.left - frame_width).max(0) as u32;
+        let mut height = (rect.bottom - rect.top - frame_height).max(0) as u32;
+
+        if let Some((width_inc, height_inc)) = state.resize_increments.get() {
+            // Like on X11, the increments count from the minimum size.
+            let (base_width, base_height) = state.min_inner_size.get().unwrap_or((0, 0));
+
+            if width_inc > 0 && width > base_width {
+                width -= (width - base_width) % width_inc;
+            }
+
+            if height_inc > 0 && height > base_height {
+                height -= (height - base_height) % height_inc;
+            }
+        }
+
+        // Windows clamps to the minimum and maximum size after this, which would break the aspect ratio.
+        let min_size = state.min_inner_size.get().unwrap_or((0, 0));
+        let max_size = state.max_inner_size.get().unwrap_or((u32::MAX, u32::MAX));
+        let clamp_width = |width: u32| width.min(max_size.0).max(min_size.0);
+        let clamp_height = |height: u32| height.min(max_size.1).max(min_size.1);
+
+        width = clamp_width(width);
+        height = clamp_height(height);
+
+        if let Some((numerator, denominator)) = state.aspect_ratio.get().filter(|&(n, d)| n > 0 && d > 0) {
+            // Dragging the top or bottom edge changes the height, any other edge or corner the width. When the other
+            // side leaves its limits, it is clamped and the dragged side follows it instead.
+            if edge == WMSZ_TOP || edge == WMSZ_BOTTOM {
+                width = clamp_width(height * numerator / denominator);
+                height = width * denominator / numerator;
+            } else {
+                height = clamp_height(width * denominator / numerator);
+                width = height * numerator / denominator;
+            }
+        }
+
+        let width = width as i32 + frame_width;
+        let height = height as i32 + frame_height;
+
+        // Keep the edges opposite to the dragged ones in place.
+        if matches!(edge, WMSZ_LEFT | WMSZ_TOPLEFT | WMSZ_BOTTOMLEFT) {
+            rect.left = rect.right - width;
+        } else {
+            rect.right = rect.left + width;
+        }
+
+        if matches!(edge, WMSZ_TOP | WMSZ_TOPLEFT | WMSZ_TOPRIGHT) {
+            rect.top = rect.bottom - height;
+        } else {
+            rect.bottom = rect.top + height;
+        }
+    }
+
+    /// Makes the process aware of the DPI of each monitor. Without DPI awareness, Windows stretches the window on high
+    /// DPI monitors and reports a scale factor of 1. The awareness applies to the whole process, so it is set once,
+    /// and fails if it was already set, such as by the application manifest. Windows before 10 1703 lack
+    /// SetProcessDpiAwarenessContext and keep the awareness of the manifest.
+    fn enable_dpi_awareness_win32() {
+        static ENABLE_DPI_AWARENESS: Once = Once::new();
+
+        ENABLE_DPI_AWARENESS.call_once(|| {
+            let Some(function) = Self::user32_function_win32(c"SetProcessDpiAwarenessContext") else { return };
+
+            unsafe {
+                let function = std::mem::transmute::<
+                    unsafe extern "system" fn() -> isize,
+                    unsafe extern "system" fn(DPI_AWARENESS_CONTEXT) -> BOOL,
+                >(function);
+
+                function(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+            }
+        });
+    }
+
+    /// Returns the DPI of the window. Windows before 10 1607 lack GetDpiForWindow, there it is the DPI of the monitor
+    /// showing the window.
+    fn dpi_win32(&self) -> u32 {
+        if let Some(function) = Self::user32_function_win32(c"GetDpiForWindow") {
+            unsafe {
+                let function = std::mem::transmute::<
+                    unsafe extern "system" fn() -> isize,
+                    unsafe extern "system" fn(HWND) -> u32,
+                >(function);
+
+                return function(self.hwnd);
+            }
+        }
+
+        let (mut dpi_x, mut dpi_y) = (96, 96);
+
+        unsafe {
+            GetDpiForMonitor(
+                MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST), MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y,
+            );
+        }
+
+        dpi_x
+    }
+
+    /// Looks up the user32 function `name`, which the oldest supported Windows versions may lack. Linking to it would
+    /// keep the program from starting there.
+    fn user32_function_win32(name: &CStr) -> Option<unsafe extern "system" fn() -> isize> {
+        unsafe { GetProcAddress(GetModuleHandleA(c"user32.dll".as_ptr().cast()), name.as_ptr().cast()) }
+    }
+
+    fn inner_size_win32(&self) -> PhysicalSize {
+        Self::client_size_win32(self.hwnd)
+    }
+
+    fn client_size_win32(hwnd: HWND) -> PhysicalSize {
+        let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+        unsafe { GetClientRect(hwnd, &mut rect); }
+
+        PhysicalSize::new((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32)
+    }
+
+    fn set_inner_size_win32(&self, width: u32, height: u32) {
+        let (width, height) = Self::win32_outer_size(self.hwnd, (width, height));
+        let flags = SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE;
+
+        if unsafe { SetWindowPos(self.hwnd, 0, 0, 0, width, height, flags) } == 0 {
+            log::warn!("Failed to resize the window: {}", unsafe { GetLastError() });
+        }
+    }
+
+    /// Resizes the window to its current size, which makes Windows apply a changed minimum and maximum size.
+    fn apply_size_constraints_win32(&self) {
+        let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+        let flags = SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE;
+
+        unsafe {
+            if GetWindowRect(self.hwnd, &mut rect) == 0
+                || SetWindowPos(self.hwnd, 0, 0, 0, rect.right - rect.left, rect.bottom - rect.top, flags) == 0 {
+                log::warn!("Failed to apply the window size constraints: {}", GetLastError());
+            }
+        }
+    }
+
+    /// Returns the rectangle of the monitor containing `point`, or of the primary monitor if no monitor does.
+    fn win32_monitor_rect(point: (i32, i32)) -> RECT {
+        let monitor = unsafe { MonitorFromPoint(POINT { x: point.0, y: point.1 }, MONITOR_DEFAULTTOPRIMARY) };
+
+        let mut info = MaybeUninit::<MONITORINFO>::zeroed();
+        unsafe {
+            (*info.as_mut_ptr()).cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+            GetMonitorInfoW(monitor, info.as_mut_ptr());
+            info.assume_init().rcMonitor
+        }
+    }
+
+    /// Returns the monitor `handle`, or `None` if it could not be queried.
+    fn monitor_win32(handle: HMONITOR) -> Option<Monitor> {
+        let mut info: MONITORINFOEXW = unsafe { std::mem::zeroed() };
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+
+        if unsafe { GetMonitorInfoW(handle, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO) } == 0 {
+            return None;
+        }
+
+        let rect = info.monitorInfo.rcMonitor;
+        let device_name = info.szDevice;
+        let name_len = device_name.iter().position(|&c| c == 0).unwrap_or(device_name.len());
+
+        let mut current: DEVMODEW = unsafe { std::mem::zeroed() };
+        current.dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+
+        // Rates of 0 and 1 stand for the default rate of the hardware.
+        let refresh_rate_millihertz = match unsafe {
+            EnumDisplaySettingsW(device_name.as_ptr(), ENUM_CURRENT_SETTINGS, &mut current)
+        } {
+            0 => None,
+            _ => Some(current.dmDisplayFrequency).filter(|&hertz| hertz > 1).map(|hertz| hertz * 1000),
+        };
+
+        let size_mm = unsafe {
+            let dc = CreateDCW(device_name.as_ptr(), ptr::null(), ptr::null(), ptr::null());
+
+            if dc == 0 {
+                (0, 0)
+            } else {
+                let size = (GetDeviceCaps(dc, HORZSIZE as i32) as u32, GetDeviceCaps(dc, VERTSIZE as i32) as u32);
+                DeleteDC(dc);
+                size
+            }
+        };
+
+        let (mut dpi_x, mut dpi_y) = (0, 0);
+        let scale_factor = match unsafe { GetDpiForMonitor(handle, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) } {
+            0 => dpi_x as f64 / 96.0,
+            _ => 1.0,
+        };
+
+        let mut video_modes: Vec<VideoMode> = Vec::new();
+
+        for index in 0.. {
+            let mut dev_mode: DEVMODEW = unsafe { std::mem::zeroed() };
+            dev_mode.dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+
+            if unsafe { EnumDisplaySettingsW(device_name.as_ptr(), index, &mut dev_mode) } == 0 {
+                break;
+            }
+
+            // Modes that differ in scaling or orientation only are listed more than once.
+            let video_mode = VideoMode {
+                size: (dev_mode.dmPelsWidth, dev_mode.dmPelsHeight),
+                bit_depth: dev_mode.dmBitsPerPel as u16,
+                refresh_rate_millihertz: dev_mode.dmDisplayFrequency * 1000,
+                device_name,
+            };
+
+            if !video_modes.contains(&video_mode) {
+                video_modes.push(video_mode);
+            }
+        }
+
+        Some(Monitor {
+            name: String::from_utf16_lossy(&device_name[..name_len]),
+            position: (rect.left, rect.top),
+            size: ((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32),
+            size_mm,
+            refresh_rate_millihertz,
+            scale_factor,
+            primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+            video_modes,
+            handle,
+        })
+    }
+
+    fn monitors_win32() -> Vec<Monitor> {
+        unsafe extern "system" fn push_monitor(handle: HMONITOR, _: HDC, _: *mut RECT, handles: LPARAM) -> BOOL {
+            (*(handles as *mut Vec<HMONITOR>)).push(handle);
+            1
         }
-    }
 
-    fn raw_window_handle_linux_x(&self) -> RawWindowHandle {
-        let handle = XcbWindowHandle::new(NonZeroU32::new(self.window).unwrap());
+        let mut handles: Vec<HMONITOR> = Vec::new();
+        unsafe {
+            EnumDisplayMonitors(0, ptr::null(), Some(push_monitor), &mut handles as *mut Vec<HMONITOR> as LPARAM);
+        }
 
-        RawWindowHandle::Xcb(handle)
+        handles.into_iter().filter_map(Self::monitor_win32).collect()
     }
 
-    fn raw_display_handle_linux_x(&self) -> RawDisplayHandle {
-        let handle = XcbDisplayHandle::new(
-            Some(NonNull::new(self.connection.get_raw_conn() as *mut c_void).unwrap()), self.screen
-        );
+    fn set_fullscreen_win32(&mut self, fullscreen: Option<&Fullscreen>) -> Result<(), WindowError> {
+        let video_mode = match fullscreen {
+            Some(Fullscreen::Exclusive(video_mode)) => Some(video_mode),
+            _ => None,
+        };
 
-        RawDisplayHandle::Xcb(handle)
-    }
+        // A switch to another monitor leaves the switched one in its original video mode.
+        if let Some(device_name) = self.switched_display {
+            if video_mode.map(|video_mode| video_mode.device_name) != Some(device_name) {
+                self.restore_video_mode_win32();
+            }
+        }
 
-    fn translate_key_code(&self, x_keycode: x::Keycode) -> Option<Keys> {
+        let rect = match fullscreen {
+            None => {
+                if let Some((style, rect)) = self.windowed.take() {
+                    let flags = SWP_FRAMECHANGED | SWP_NOZORDER | SWP_NOACTIVATE;
 
-        let key_sym = unsafe {
-            x11::xlib::XkbKeycodeToKeysym(
-                self.connection.get_raw_dpy(),
-                x_keycode as x11::xlib::KeyCode,
-                0,
-                if x_keycode as u32 & x11::xlib::ShiftMask == 0 { 1 } else { 0 }
-            )
+                    unsafe {
+                        SetWindowLongW(self.hwnd, GWL_STYLE, style as i32);
+                        SetWindowPos(
+                            self.hwnd, 0, rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top, flags
+                        );
+                    }
+                }
+
+                return Ok(());
+            },
+            Some(Fullscreen::Borderless(Some(monitor))) => RECT {
+                left: monitor.position.0,
+                top: monitor.position.1,
+                right: monitor.position.0 + monitor.size.0 as i32,
+                bottom: monitor.position.1 + monitor.size.1 as i32,
+            },
+            Some(Fullscreen::Borderless(None)) => {
+                let monitor = unsafe { MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST) };
+                let monitor = Self::monitor_win32(monitor).ok_or_else(|| WindowError::Os(unsafe { GetLastError() }))?;
+
+                RECT {
+                    left: monitor.position.0,
+                    top: monitor.position.1,
+                    right: monitor.position.0 + monitor.size.0 as i32,
+                    bottom: monitor.position.1 + monitor.size.1 as i32,
+                }
+            },
+            Some(Fullscreen::Exclusive(video_mode)) => self.switch_video_mode_win32(video_mode)?,
         };
 
-        match key_sym as c_uint {
-            x11::keysym::XK_BackSpace => Some(Keys::Backspace),
-            x11::keysym::XK_Return => Some(Keys::Enter),
-            x11::keysym::XK_Tab => Some(Keys::Tab),
-                //x11::keysym::XK_Shift: return keys::SHIFT),
-                //x11::keysym::XK_Control: return keys::CONTROL),
-
-            x11::keysym::XK_Pause => Some(Keys::Pause),
-            x11::keysym::XK_Caps_Lock => Some(Keys::Capital),
-
-            x11::keysym::XK_Escape => Some(Keys::Escape),
-
-                // Not supported
-                // case : return keys::CONVERT),
-                // case : return keys::NONCONVERT),
-                // case : return keys::ACCEPT),
-
-            x11::keysym::XK_Mode_switch => Some(Keys::Modechange),
-
-            x11::keysym::XK_space => Some(Keys::Space),
-            x11::keysym::XK_Prior => Some(Keys::Prior),
-            x11::keysym::XK_Next => Some(Keys::Next),
-            x11::keysym::XK_End => Some(Keys::End),
-            x11::keysym::XK_Home => Some(Keys::Home),
-            x11::keysym::XK_Left => Some(Keys::Left),
-            x11::keysym::XK_Up => Some(Keys::Up),
-            x11::keysym::XK_Right => Some(Keys::Right),
-            x11::keysym::XK_Down => Some(Keys::Down),
-            x11::keysym::XK_Select => Some(Keys::Select),
-            x11::keysym::XK_Print => Some(Keys::Print),
-            x11::keysym::XK_Execute => Some(Keys::Execute),
-            // x11::keysym::XK_snapshot: return keys::SNAPSHOT), // not supported
-            x11::keysym::XK_Insert => Some(Keys::Insert),
-            x11::keysym::XK_Delete => Some(Keys::Delete),
-            x11::keysym::XK_Help => Some(Keys::Help),
-
-            x11::keysym::XK_Super_L => Some(Keys::LWin),
-            x11::keysym::XK_Super_R => Some(Keys::RWin),
-                // x11::keysym::XK_apps: return keys::APPS), // not supported
-
-                // x11::keysym::XK_sleep: return keys::SLEEP), //not supported
-
-            x11::keysym::XK_KP_0 => Some(Keys::Numpad0),
-            x11::keysym::XK_KP_1 => Some(Keys::Numpad1),
-            x11::keysym::XK_KP_2 => Some(Keys::Numpad2),
-            x11::keysym::XK_KP_3 => Some(Keys::Numpad3),
-            x11::keysym::XK_KP_4 => Some(Keys::Numpad4),
-            x11::keysym::XK_KP_5 => Some(Keys::Numpad5),
-            x11::keysym::XK_KP_6 => Some(Keys::Numpad6),
-            x11::keysym::XK_KP_7 => Some(Keys::Numpad7),
-            x11::keysym::XK_KP_8 => Some(Keys::Numpad8),
-            x11::keysym::XK_KP_9 => Some(Keys::Numpad9),
-            x11::keysym::XK_multiply => Some(Keys::Multiply),
-            x11::keysym::XK_KP_Add => Some(Keys::Add),
-            x11::keysym::XK_KP_Separator => Some(Keys::Separator),
-            x11::keysym::XK_KP_Subtract => Some(Keys::Subtract),
-            x11::keysym::XK_KP_Decimal => Some(Keys::Decimal),
-            x11::keysym::XK_KP_Divide => Some(Keys::Divide),
-            x11::keysym::XK_F1 => Some(Keys::F1),
-            x11::keysym::XK_F2 => Some(Keys::F2),
-            x11::keysym::XK_F3 => Some(Keys::F3),
-            x11::keysym::XK_F4 => Some(Keys::F4),
-            x11::keysym::XK_F5 => Some(Keys::F5),
-            x11::keysym::XK_F6 => Some(Keys::F6),
-            x11::keysym::XK_F7 => Some(Keys::F7),
-            x11::keysym::XK_F8 => Some(Keys::F8),
-            x11::keysym::XK_F9 => Some(Keys::F9),
-            x11::keysym::XK_F10 => Some(Keys::F10),
-            x11::keysym::XK_F11 => Some(Keys::F11),
-            x11::keysym::XK_F12 => Some(Keys::F12),
-            x11::keysym::XK_F13 => Some(Keys::F13),
-            x11::keysym::XK_F14 => Some(Keys::F14),
-            x11::keysym::XK_F15 => Some(Keys::F15),
-            x11::keysym::XK_F16 => Some(Keys::F16),
-            x11::keysym::XK_F17 => Some(Keys::F17),
-            x11::keysym::XK_F18 => Some(Keys::F18),
-            x11::keysym::XK_F19 => Some(Keys::F19),
-            x11::keysym::XK_F20 => Some(Keys::F20),
-            x11::keysym::XK_F21 => Some(Keys::F21),
-            x11::keysym::XK_F22 => Some(Keys::F22),
-            x11::keysym::XK_F23 => Some(Keys::F23),
-            x11::keysym::XK_F24 => Some(Keys::F24),
-
-            x11::keysym::XK_Num_Lock => Some(Keys::Numlock),
-            x11::keysym::XK_Scroll_Lock => Some(Keys::Scroll),
-
-            x11::keysym::XK_KP_Equal => Some(Keys::NumpadEqual),
-
-            x11::keysym::XK_Shift_L => Some(Keys::LShift),
-            x11::keysym::XK_Shift_R => Some(Keys::RShift),
-            x11::keysym::XK_Control_L => Some(Keys::LControl),
-            x11::keysym::XK_Control_R => Some(Keys::RControl),
-            // x11::keysym::XK_Menu: return keys::LMENU),
-            x11::keysym::XK_Menu => Some(Keys::RMenu),
-
-            x11::keysym::XK_semicolon => Some(Keys::Semicolon),
-            x11::keysym::XK_plus => Some(Keys::Plus),
-            x11::keysym::XK_comma => Some(Keys::Comma),
-            x11::keysym::XK_minus => Some(Keys::Minus),
-            x11::keysym::XK_period => Some(Keys::Period),
-            x11::keysym::XK_slash => Some(Keys::Slash),
-            x11::keysym::XK_grave => Some(Keys::Grave),
-
-            x11::keysym::XK_a | x11::keysym::XK_A => Some(Keys::A),
-            x11::keysym::XK_b | x11::keysym::XK_B => Some(Keys::B),
-            x11::keysym::XK_c | x11::keysym::XK_C => Some(Keys::C),
-            x11::keysym::XK_d | x11::keysym::XK_D => Some(Keys::D),
-            x11::keysym::XK_e | x11::keysym::XK_E => Some(Keys::E),
-            x11::keysym::XK_f | x11::keysym::XK_F => Some(Keys::F),
-            x11::keysym::XK_g | x11::keysym::XK_G => Some(Keys::G),
-            x11::keysym::XK_h | x11::keysym::XK_H => Some(Keys::H),
-            x11::keysym::XK_i | x11::keysym::XK_I => Some(Keys::I),
-            x11::keysym::XK_j | x11::keysym::XK_J => Some(Keys::J),
-            x11::keysym::XK_k | x11::keysym::XK_K => Some(Keys::K),
-            x11::keysym::XK_l | x11::keysym::XK_L => Some(Keys::L),
-            x11::keysym::XK_m | x11::keysym::XK_M => Some(Keys::M),
-            x11::keysym::XK_n | x11::keysym::XK_N => Some(Keys::N),
-            x11::keysym::XK_o | x11::keysym::XK_O => Some(Keys::O),
-            x11::keysym::XK_p | x11::keysym::XK_P => Some(Keys::P),
-            x11::keysym::XK_q | x11::keysym::XK_Q => Some(Keys::Q),
-            x11::keysym::XK_r | x11::keysym::XK_R => Some(Keys::R),
-            x11::keysym::XK_s | x11::keysym::XK_S => Some(Keys::S),
-            x11::keysym::XK_t | x11::keysym::XK_T => Some(Keys::T),
-            x11::keysym::XK_u | x11::keysym::XK_U => Some(Keys::U),
-            x11::keysym::XK_v | x11::keysym::XK_V => Some(Keys::V),
-            x11::keysym::XK_w | x11::keysym::XK_W => Some(Keys::W),
-            x11::keysym::XK_x | x11::keysym::XK_X => Some(Keys::X),
-            x11::keysym::XK_y | x11::keysym::XK_Y => Some(Keys::Y),
-            x11::keysym::XK_z | x11::keysym::XK_Z => Some(Keys::Z),
-            _ => { log::warn!("Unrecognized x keysym: {}", key_sym); None }
-        }
-    }
-}
+        if self.windowed.is_none() {
+            let style = unsafe { GetWindowLongW(self.hwnd, GWL_STYLE) } as u32;
+            let mut windowed_rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
 
+            if unsafe { GetWindowRect(self.hwnd, &mut windowed_rect) } == 0 {
+                return Err(WindowError::Os(unsafe { GetLastError() }));
+            }
 
-#[cfg(target_os = "windows")]
-impl Window {
-    pub const WINDOW_CLASS_NAME: &'static str = "window_class";
+            self.windowed = Some((style, windowed_rect));
+        }
 
-    fn new_win32(
-        window_name: &str,
-        x: i32, y: i32,
-        width: i32, height: i32,
-    ) -> Self {
-        let window_class_name_utf16 = Self::wide_null(Self::WINDOW_CLASS_NAME);
-        let application_name_utf16 = Self::wide_null(window_name);
+        // Keep the state bits of the style, such as WS_VISIBLE, and replace the frame.
+        let style = (unsafe { GetWindowLongW(self.hwnd, GWL_STYLE) } as u32 & !WS_OVERLAPPEDWINDOW) | WS_POPUP;
+        let flags = SWP_FRAMECHANGED | SWP_NOACTIVATE;
 
-        let h_instance = unsafe { GetModuleHandleA(ptr::null()) };
+        unsafe {
+            SetWindowLongW(self.hwnd, GWL_STYLE, style as i32);
 
-        let icon = unsafe { LoadIconW(h_instance, IDI_APPLICATION) };
+            if SetWindowPos(
+                self.hwnd, HWND_TOP, rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top, flags
+            ) == 0 {
+                return Err(WindowError::Os(GetLastError()));
+            }
+        }
 
-        let wc = WNDCLASSW {
-            style: CS_DBLCLKS,
-            lpfnWndProc: Some(win32_process_message),
-            cbClsExtra: 0,
-            cbWndExtra: 0,
-            hInstance: h_instance,
-            hIcon: icon,
-            hCursor: unsafe { LoadCursorW(0, IDC_ARROW) },
-            hbrBackground: 0,
-            lpszClassName: window_class_name_utf16.as_ptr(),
-            lpszMenuName: ptr::null(),
+        Ok(())
+    }
+
+    /// Switches the display device of `video_mode` to it, and returns the rectangle of the monitor afterwards.
+    fn switch_video_mode_win32(&mut self, video_mode: &VideoMode) -> Result<RECT, WindowError> {
+        let mut dev_mode: DEVMODEW = unsafe { std::mem::zeroed() };
+        dev_mode.dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+        dev_mode.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT | DM_BITSPERPEL | DM_DISPLAYFREQUENCY;
+        dev_mode.dmPelsWidth = video_mode.size.0;
+        dev_mode.dmPelsHeight = video_mode.size.1;
+        dev_mode.dmBitsPerPel = video_mode.bit_depth as u32;
+        dev_mode.dmDisplayFrequency = video_mode.refresh_rate_millihertz / 1000;
+
+        // CDS_FULLSCREEN makes the change temporary: Windows restores the original mode when the process exits.
+        let result = unsafe {
+            ChangeDisplaySettingsExW(video_mode.device_name.as_ptr(), &dev_mode, 0, CDS_FULLSCREEN, ptr::null())
         };
 
-        if unsafe { RegisterClassW(&wc) } == 0 {
-            unsafe {
-                MessageBoxA(
-                    0,
-                    "Window registration failed.".as_ptr(),
-                    "Error".as_ptr(),
-                    MB_ICONEXCLAMATION | MB_OK
-                );
-            }
+        if result != DISP_CHANGE_SUCCESSFUL {
+            return Err(WindowError::Os(result as u32));
+        }
+
+        self.switched_display = Some(video_mode.device_name);
 
-            log::error!("Window registration failed.");
-            panic!("Window registration failed.");
+        // The monitor keeps its position on the desktop.
+        let mut current: DEVMODEW = unsafe { std::mem::zeroed() };
+        current.dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+
+        if unsafe { EnumDisplaySettingsW(video_mode.device_name.as_ptr(), ENUM_CURRENT_SETTINGS, &mut current) } == 0 {
+            return Err(WindowError::Os(unsafe { GetLastError() }));
         }
 
-        let client_x = x;
-        let client_y = y;
-        let client_width = width;
-        let client_height = height;
+        let position = unsafe { current.Anonymous1.Anonymous2.dmPosition };
 
-        let mut window_x = client_x;
-        let mut window_y = client_y;
-        let mut window_width = client_width;
-        let mut window_height = client_height;
+        Ok(RECT {
+            left: position.x,
+            top: position.y,
+            right: position.x + video_mode.size.0 as i32,
+            bottom: position.y + video_mode.size.1 as i32,
+        })
+    }
 
-        let window_style = WS_OVERLAPPED | WS_SYSMENU | WS_CAPTION | WS_MAXIMIZEBOX | WS_MINIMIZEBOX | WS_THICKFRAME;
-        let window_ex_style = WS_EX_APPWINDOW;
+    /// Switches the display device changed by exclusive fullscreen back to its original video mode.
+    fn restore_video_mode_win32(&mut self) {
+        let Some(device_name) = self.switched_display.take() else { return };
 
-        let mut border_rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
-        unsafe { AdjustWindowRectEx(&mut border_rect, window_style, 0, window_ex_style); }
+        let result = unsafe { ChangeDisplaySettingsExW(device_name.as_ptr(), ptr::null(), 0, 0, ptr::null()) };
 
-        window_x += border_rect.left;
-        window_y += border_rect.top;
-        window_width += border_rect.right - border_rect.left;
-        window_height += border_rect.bottom - border_rect.top;
+        if result != DISP_CHANGE_SUCCESSFUL {
+            log::warn!("Failed to restore the video mode: {}", result);
+        }
+    }
 
-        let handle = unsafe {
-            CreateWindowExW(
-                window_ex_style, window_class_name_utf16.as_ptr(), application_name_utf16.as_ptr(),
-                window_style, window_x, window_y, window_width, window_height,
-                0, 0, h_instance, ptr::null()
-            )
+    fn poll_messages_win32(&mut self, mut event_closure: impl FnMut(WindowEvent)) -> Result<(), WindowError> {
+        // Messages handled inside DispatchMessageW, such as the resizes during the modal loop Windows runs while the
+        // user drags the window border, are reported by the window procedure through the window state. The guard
+        // unsets the pointer again before the closure goes out of scope.
+        let event_closure: &mut dyn FnMut(WindowEvent) = &mut event_closure;
+        let event_closure = unsafe {
+            std::mem::transmute::<&mut dyn FnMut(WindowEvent), *mut (dyn FnMut(WindowEvent) + 'static)>(event_closure)
         };
 
-        if handle == 0 {
-            unsafe {
-                MessageBoxA(
-                    0,
-                    "Window creation failed.".as_ptr(),
-                    "Error".as_ptr(),
-                    MB_ICONEXCLAMATION | MB_OK
-                );
-            }
+        self.state.event_closure.set(Some(event_closure));
+        let _guard = EventClosureGuard(&*self.state);
 
-            log::error!("Window creation failed.");
-            panic!("Window creation failed.");
-        }
+        let result = self.dispatch_messages_win32();
 
-        // Show the window.
-        let should_activate = true;
-        let show_window_command_flags = if should_activate { SW_SHOW } else { SW_SHOWNOACTIVATE };
+        // Windows sends a display change for each step of a reconfiguration.
+        if self.state.monitors_changed.take() {
+            self.state.emit(WindowEvent::MonitorsChanged);
+        }
 
-        unsafe { ShowWindow(handle, show_window_command_flags); }
+        result
+    }
 
-        Self {
-            previous_size: (window_width as u32, window_height as u32),
-            h_instance,
-            hwnd: handle,
+    /// Takes the damage the modal loop collected during the last [`Window::poll_messages`] call. Returns whether the
+    /// modal loop sent the [`WindowEvent::RedrawRequested`] of the call, the damage after that waits for the next one.
+    fn take_modal_loop_damage_win32(&mut self) -> bool {
+        for rect in self.state.damage.take() {
+            Self::add_damage(&mut self.pending_damage, rect);
         }
+
+        let Some(damage) = self.state.reported_damage.take() else { return false };
+
+        self.damage = damage;
+        self.redraw_requested = false;
+
+        true
     }
 
-    fn poll_messages_win32(&mut self, mut event_closure: impl FnMut(WindowEvent)) {
+    fn dispatch_messages_win32(&mut self) -> Result<(), WindowError> {
+        let event_closure = |event: WindowEvent| { self.state.emit(event); };
         let mut message = MaybeUninit::<MSG>::uninit();
 
         while unsafe { PeekMessageW(message.as_mut_ptr(), self.hwnd, 0, 0, PM_REMOVE) } != 0 {
             unsafe {
-                if !(message.assume_init().message == CUSTOM_CLOSE_MESSAGE
-                    || message.assume_init().message == CUSTOM_SIZE_MESSAGE) {
+                // Everything but WM_PAINT, the custom messages included, is handled by the window procedure.
+                if message.assume_init().message != WM_PAINT {
                     TranslateMessage(message.as_mut_ptr());
                     DispatchMessageW(message.as_mut_ptr());
                 }
             }
             
             match unsafe { message.assume_init().message } {
-                CUSTOM_CLOSE_MESSAGE => {
-                    (event_closure)(WindowEvent::Close);
+                WM_PAINT => {
+                    let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+
+                    // Validating the update region stops Windows from sending WM_PAINT until the next damage.
+                    unsafe {
+                        GetUpdateRect(self.hwnd, &mut rect, 0);
+                        ValidateRect(self.hwnd, ptr::null());
+                    }
+
+                    Self::add_damage(&mut self.pending_damage, Rect {
+                        x: rect.left,
+                        y: rect.top,
+                        width: (rect.right - rect.left) as u32,
+                        height: (rect.bottom - rect.top) as u32,
+                    });
                 },
-                CUSTOM_SIZE_MESSAGE => {
-                    let mut r = MaybeUninit::<RECT>::uninit();
-                    unsafe { GetClientRect(self.hwnd, r.as_mut_ptr()); }
+                WM_CHAR => {
+                    // Characters outside the BMP arrive as two messages, one per UTF-16 surrogate.
+                    let unit = unsafe { message.assume_init().wParam } as u16;
+
+                    let units = match self.high_surrogate.take() {
+                        Some(high) => vec![high, unit],
+                        None if (0xD800..0xDC00).contains(&unit) => {
+                            self.high_surrogate = Some(unit);
+                            continue;
+                        },
+                        None => vec![unit],
+                    };
 
+                    let text: String = char::decode_utf16(units)
+                        .filter_map(|c| c.ok())
+                        .filter(|c| !c.is_control())
+                        .collect();
 
-                    let width = unsafe { r.assume_init().right - r.assume_init().left } as u32;
-                    let height = unsafe { r.assume_init().bottom - r.assume_init().top } as u32;
-                    
-                    if self.previous_size != (width, height) {
-                        self.previous_size = (width, height);
-                        (event_closure)(WindowEvent::Resize(width, height));
+                    if !text.is_empty() {
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::Text(text)));
                     }
                 },
                 WM_MOUSEMOVE => {
-                    let mouse_pos = utility::get_x_y_lparam(unsafe{ message.assume_init().lParam });
-                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseMove(mouse_pos.0, mouse_pos.1)));
+                    // Windows has no enter message, the first move after entering requests the leave message instead.
+                    if !self.tracking_mouse_leave {
+                        let mut track = TRACKMOUSEEVENT {
+                            cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as u32,
+                            dwFlags: TME_LEAVE,
+                            hwndTrack: self.hwnd,
+                            dwHoverTime: 0,
+                        };
+
+                        if unsafe { TrackMouseEvent(&mut track) } == 0 {
+                            log::warn!("Failed to track the mouse leaving the window: {}", unsafe { GetLastError() });
+                        }
+
+                        self.tracking_mouse_leave = true;
+
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::CursorEntered));
+                    }
+
+                    let (x, y) = utility::get_x_y_lparam(unsafe{ message.assume_init().lParam });
+                    let modifiers = Self::modifiers_win32();
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseMove { x, y, modifiers }));
+                },
+                WM_MOUSELEAVE => {
+                    self.tracking_mouse_leave = false;
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::CursorLeft));
                 },
                 WM_KEYDOWN | WM_SYSKEYDOWN => {
-                    // Check for repeats and prevent sending.
-                    if ((unsafe { message.assume_init().lParam } >> 30) & 1) as u8 == 0 {
-                        let key = Keys::from_usize(unsafe { message.assume_init().wParam });
-                        (event_closure)(WindowEvent::Input(WindowInputEvent::KeyDown(key)));
-                    }
+                    // Bit 30 holds the previous key state, which is set for repeats.
+                    let repeat = ((unsafe { message.assume_init().lParam } >> 30) & 1) as u8 == 1;
+                    let key = Self::translate_virtual_key(unsafe { message.assume_init() });
+                    let scancode = Self::translate_scancode(unsafe { message.assume_init().lParam });
+                    let modifiers = Self::modifiers_win32();
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::KeyDown { key, scancode, modifiers, repeat }));
                 },
                 WM_KEYUP | WM_SYSKEYUP => {
-                    let key = Keys::from_usize(unsafe { message.assume_init().wParam });
-                    (event_closure)(WindowEvent::Input(WindowInputEvent::KeyUp(key)));
+                    let key = Self::translate_virtual_key(unsafe { message.assume_init() });
+                    let scancode = Self::translate_scancode(unsafe { message.assume_init().lParam });
+                    let modifiers = Self::modifiers_win32();
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::KeyUp { key, scancode, modifiers }));
                 },
-                WM_MOUSEWHEEL => {
-                    let dz = if utility::get_wheel_delta_wparam(unsafe { message.assume_init().wParam }) < 0 {
-                        -1i16
-                    } else {
-                        1i16
-                    };
+                msg @ (WM_MOUSEWHEEL | WM_MOUSEHWHEEL) => {
+                    // A notch of the wheel is WHEEL_DELTA, high resolution wheels and touchpads send fractions of it.
+                    let delta = utility::get_wheel_delta_wparam(unsafe { message.assume_init().wParam }) as f64
+                        / WHEEL_DELTA as f64;
 
-                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseWheelMove(dz)));
+                    let (delta_x, delta_y) = if msg == WM_MOUSEWHEEL { (0.0, delta) } else { (delta, 0.0) };
+
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseWheel {
+                        delta_x, delta_y, unit: ScrollUnit::Lines
+                    }));
+                },
+                msg @ (WM_LBUTTONDOWN | WM_MBUTTONDOWN | WM_RBUTTONDOWN) => {
+                    let button = match msg {
+                        WM_LBUTTONDOWN => MouseButton::Left,
+                        WM_MBUTTONDOWN => MouseButton::Middle,
+                        _ => MouseButton::Right,
+                    };
+                    let modifiers = Self::modifiers_win32();
+                    let click_count = Self::click_count_win32(
+                        &mut self.click_counter, button, unsafe { &message.assume_init() }
+                    );
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown { button, modifiers, click_count }));
+                },
+                msg @ (WM_LBUTTONUP | WM_MBUTTONUP | WM_RBUTTONUP) => {
+                    let button = match msg {
+                        WM_LBUTTONUP => MouseButton::Left,
+                        WM_MBUTTONUP => MouseButton::Middle,
+                        _ => MouseButton::Right,
+                    };
+                    let modifiers = Self::modifiers_win32();
+                    (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp { button, modifiers }));
+                },
+                msg @ (WM_XBUTTONDOWN | WM_XBUTTONUP) => {
+                    let button = match utility::get_xbutton_wparam(unsafe { message.assume_init().wParam }) {
+                        XBUTTON1 => MouseButton::Back,
+                        XBUTTON2 => MouseButton::Forward,
+                        _ => continue,
+                    };
+                    let modifiers = Self::modifiers_win32();
+
+                    if msg == WM_XBUTTONDOWN {
+                        let click_count = Self::click_count_win32(
+                            &mut self.click_counter, button, unsafe { &message.assume_init() }
+                        );
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown {
+                            button, modifiers, click_count
+                        }));
+                    } else {
+                        (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp { button, modifiers }));
+                    }
                 },
-                WM_LBUTTONDOWN => (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown(MouseButton::Left))),
-                WM_MBUTTONDOWN => (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown(MouseButton::Middle))),
-                WM_RBUTTONDOWN => (event_closure)(WindowEvent::Input(WindowInputEvent::MouseDown(MouseButton::Right))),
-                WM_LBUTTONUP => (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp(MouseButton::Left))),
-                WM_MBUTTONUP => (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp(MouseButton::Middle))),
-                WM_RBUTTONUP => (event_closure)(WindowEvent::Input(WindowInputEvent::MouseUp(MouseButton::Right))),
                 _ => (),
             }
 
         }
+
+        // The client area may have moved or changed size, inside DispatchMessageW as well.
+        if self.cursor_grab == CursorGrabMode::Confined && unsafe { GetFocus() } == self.hwnd {
+            self.update_cursor_grab_win32();
+        }
+
+        Ok(())
+    }
+
+    /// Translates the virtual key of a key message, telling the left and right Shift, Control and Alt keys apart
+    /// like the X11 backend does.
+    fn translate_virtual_key(message: MSG) -> Option<Keys> {
+        let extended = (message.lParam >> 24) & 1 == 1;
+
+        let vk = match message.wParam as VIRTUAL_KEY {
+            VK_SHIFT => {
+                let scancode = ((message.lParam >> 16) & 0xFF) as u32;
+                unsafe { MapVirtualKeyW(scancode, MAPVK_VSC_TO_VK_EX) as VIRTUAL_KEY }
+            },
+            VK_CONTROL => if extended { VK_RCONTROL } else { VK_LCONTROL },
+            VK_MENU => if extended { VK_RMENU } else { VK_LMENU },
+            vk => vk,
+        };
+
+        let key = Keys::try_from_usize(vk as usize);
+
+        if key.is_none() {
+            log::warn!("Unrecognized virtual key: {:#x}", vk);
+        }
+
+        key
+    }
+
+    /// Translates the scancode and extended key flag in the `l_param` of a key message.
+    fn translate_scancode(l_param: LPARAM) -> ScanCode {
+        let scancode = ((l_param >> 16) & 0xFF) as u32;
+        let extended = (l_param >> 24) & 1 == 1;
+
+        ScanCode(utility::evdev_from_set1(scancode, extended))
+    }
+
+    fn key_for_scancode_win32(scancode: ScanCode) -> Option<Keys> {
+        let (set1, extended) = utility::set1_from_evdev(scancode.0)?;
+        let set1 = if extended { 0xE000 | set1 } else { set1 };
+
+        let vk = unsafe { MapVirtualKeyW(set1, MAPVK_VSC_TO_VK_EX) };
+
+        Keys::try_from_usize(vk as usize)
+    }
+
+    fn outer_position_win32(&self) -> Result<(i32, i32), WindowError> {
+        let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+
+        if unsafe { GetWindowRect(self.hwnd, &mut rect) } == 0 {
+            return Err(WindowError::Os(unsafe { GetLastError() }));
+        }
+
+        Ok((rect.left, rect.top))
+    }
+
+    fn inner_position_win32(&self) -> Result<(i32, i32), WindowError> {
+        let mut point = POINT { x: 0, y: 0 };
+
+        if unsafe { ClientToScreen(self.hwnd, &mut point) } == 0 {
+            return Err(WindowError::Os(unsafe { GetLastError() }));
+        }
+
+        Ok((point.x, point.y))
+    }
+
+    fn set_outer_position_win32(&self, x: i32, y: i32) {
+        let flags = SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE;
+
+        if unsafe { SetWindowPos(self.hwnd, 0, x, y, 0, 0, flags) } == 0 {
+            log::warn!("Failed to move the window: {}", unsafe { GetLastError() });
+        }
+    }
+
+    fn cursor_position_win32(&self) -> Option<(i16, i16)> {
+        let mut point = POINT { x: 0, y: 0 };
+
+        if unsafe { GetCursorPos(&mut point) } == 0 || unsafe { ScreenToClient(self.hwnd, &mut point) } == 0 {
+            return None;
+        }
+
+        Some((point.x.try_into().ok()?, point.y.try_into().ok()?))
+    }
+
+    /// Updates the cursor the window procedure shows over the client area for the visibility and icon of the cursor.
+    fn update_cursor_win32(&self) {
+        let cursor = match self.cursor_visible {
+            true => unsafe { LoadCursorW(0, self.cursor_icon.win32_id()) },
+            false => 0,
+        };
+
+        self.state.cursor.set(cursor);
+
+        // WM_SETCURSOR only comes with the next mouse move.
+        let (width, height) = self.state.inner_size.get();
+        let over_client_area = self.cursor_position_win32()
+            .is_some_and(|(x, y)| x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height);
+
+        if over_client_area {
+            unsafe { SetCursor(cursor); }
+        }
+    }
+
+    /// Clips the cursor for the grab mode of the window while it has focus, and releases it otherwise.
+    fn update_cursor_grab_win32(&self) {
+        self.state.cursor_grab.set(self.cursor_grab);
+
+        Self::clip_cursor_win32(self.hwnd, self.cursor_grab, unsafe { GetFocus() } == self.hwnd);
+    }
+
+    /// Clips the cursor for the grab `mode` of the window `hwnd` if it is `focused`, and releases it otherwise.
+    fn clip_cursor_win32(hwnd: HWND, mode: CursorGrabMode, focused: bool) {
+        let clip = match mode {
+            CursorGrabMode::Confined if focused => {
+                let mut rect = RECT { left: 0, right: 0, top: 0, bottom: 0 };
+                let mut origin = POINT { x: 0, y: 0 };
+
+                unsafe {
+                    GetClientRect(hwnd, &mut rect);
+                    ClientToScreen(hwnd, &mut origin);
+                }
+
+                Some(RECT {
+                    left: origin.x,
+                    top: origin.y,
+                    right: origin.x + rect.right,
+                    bottom: origin.y + rect.bottom,
+                })
+            },
+            CursorGrabMode::Locked if focused => {
+                let mut point = POINT { x: 0, y: 0 };
+                unsafe { GetCursorPos(&mut point); }
+
+                Some(RECT { left: point.x, top: point.y, right: point.x + 1, bottom: point.y + 1 })
+            },
+            _ => None,
+        };
+
+        if unsafe { ClipCursor(clip.as_ref().map_or(ptr::null(), |rect| rect)) } == 0 {
+            log::warn!("Failed to clip the cursor: {}", unsafe { GetLastError() });
+        }
+    }
+
+    fn set_raw_input_win32(&self, enabled: bool) {
+        // The generic desktop mouse, sent to the window while it is in the foreground.
+        let device = RAWINPUTDEVICE {
+            usUsagePage: 0x01,
+            usUsage: 0x02,
+            dwFlags: if enabled { 0 } else { RIDEV_REMOVE },
+            hwndTarget: if enabled { self.hwnd } else { 0 },
+        };
+
+        if unsafe { RegisterRawInputDevices(&device, 1, std::mem::size_of::<RAWINPUTDEVICE>() as u32) } == 0 {
+            log::warn!("Failed to change the raw input of the window: {}", unsafe { GetLastError() });
+        }
+    }
+
+    /// Returns the relative mouse motion in the raw input `handle` of a WM_INPUT message, or `None` if it holds
+    /// something else.
+    fn raw_mouse_motion_win32(handle: HRAWINPUT) -> Option<(f64, f64)> {
+        let mut input: RAWINPUT = unsafe { std::mem::zeroed() };
+        let mut size = std::mem::size_of::<RAWINPUT>() as u32;
+        let header_size = std::mem::size_of::<RAWINPUTHEADER>() as u32;
+
+        let read = unsafe {
+            GetRawInputData(handle, RID_INPUT, ptr::addr_of_mut!(input).cast(), &mut size, header_size)
+        };
+
+        if read == 0 || read == u32::MAX || input.header.dwType != RIM_TYPEMOUSE {
+            return None;
+        }
+
+        let mouse = unsafe { input.data.mouse };
+
+        // Tablets and remote desktop sessions report absolute positions instead.
+        if mouse.usFlags as u32 & MOUSE_MOVE_ABSOLUTE != 0 || (mouse.lLastX == 0 && mouse.lLastY == 0) {
+            return None;
+        }
+
+        Some((mouse.lLastX as f64, mouse.lLastY as f64))
+    }
+
+    /// Counts the press of `button` in the button down `message` as a click, with the double click time and rectangle
+    /// of the system.
+    fn click_count_win32(click_counter: &mut ClickCounter, button: MouseButton, message: &MSG) -> u32 {
+        let position = utility::get_x_y_lparam(message.lParam);
+        // The double click rectangle is centered on the first press.
+        let max_distance = unsafe { (GetSystemMetrics(SM_CXDOUBLECLK) / 2, GetSystemMetrics(SM_CYDOUBLECLK) / 2) };
+
+        click_counter.press(button, message.time, position, unsafe { GetDoubleClickTime() }, max_distance)
+    }
+
+    /// Returns the modifier state as of the message last retrieved from the message queue.
+    fn modifiers_win32() -> Modifiers {
+        let is_down = |key: VIRTUAL_KEY| unsafe { GetKeyState(key as i32) } as u16 & 0x8000 != 0;
+        let is_toggled = |key: VIRTUAL_KEY| unsafe { GetKeyState(key as i32) } & 1 != 0;
+
+        let mut modifiers = Modifiers::empty();
+
+        modifiers.set(Modifiers::SHIFT, is_down(VK_SHIFT));
+        modifiers.set(Modifiers::CONTROL, is_down(VK_CONTROL));
+        modifiers.set(Modifiers::ALT, is_down(VK_MENU));
+        modifiers.set(Modifiers::SUPER, is_down(VK_LWIN) || is_down(VK_RWIN));
+        modifiers.set(Modifiers::CAPS_LOCK, is_toggled(VK_CAPITAL));
+        modifiers.set(Modifiers::NUM_LOCK, is_toggled(VK_NUMLOCK));
+
+        modifiers
     }
 
     fn raw_window_handle_win32(&self) -> RawWindowHandle {
@@ -667,6 +4731,14 @@ impl Window {
         RawDisplayHandle::Windows(WindowsDisplayHandle::new())
     }
 
+    fn set_title_win32(&self, title: &str) {
+        let title = Self::wide_null(title);
+
+        if unsafe { SetWindowTextW(self.hwnd, title.as_ptr()) } == 0 {
+            log::warn!("Failed to set the window title: {}", unsafe { GetLastError() });
+        }
+    }
+
     fn wide_null(s: &str) -> Vec<u16> {
         s.encode_utf16().chain(Some(0)).collect()
     }
@@ -675,14 +4747,56 @@ impl Window {
 #[cfg(target_os = "windows")]
 impl Drop for Window {
     fn drop(&mut self) {
+        self.restore_video_mode_win32();
+
+        if self.cursor_grab != CursorGrabMode::None {
+            unsafe { ClipCursor(ptr::null()); }
+        }
+
         unsafe { DestroyWindow(self.hwnd); }
     }
 }
 
+#[cfg(target_os = "linux")]
+impl Drop for Window {
+    fn drop(&mut self) {
+        if let Err(e) = self.restore_video_mode_linux_x() {
+            log::warn!("Failed to restore the video mode: {}", e);
+        }
+
+        // The input context refers to the window, so it goes first.
+        self.input_context.take();
+        // The fields drop in order, and the connection comes before the cursor context made from it.
+        self.cursor_context.take();
+
+        if self.pointer_grabbed {
+            self.connection.send_request(&x::UngrabPointer { time: x::CURRENT_TIME });
+        }
+
+        let cookie = self.connection.send_request_checked(&x::DestroyWindow {
+            window: self.window,
+        });
+
+        if let Err(e) = self.connection.check_request(cookie) {
+            log::warn!("Failed to destroy window: {}", e);
+        }
+
+        // Discard the events still queued for the destroyed window.
+        while let Ok(Some(_)) = self.connection.poll_for_event() {}
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
+    /// The side button that navigates back, the first extra button on Windows and button 8 on X11.
+    Back,
+    /// The side button that navigates forward, the second extra button on Windows and button 9 on X11.
+    Forward,
+    /// Any other button, by its X11 button number.
+    Other(u16),
 }
 
 impl MouseButton {
@@ -691,14 +4805,56 @@ impl MouseButton {
             Self::Left => "Left",
             Self::Middle => "Middle",
             Self::Right => "Right",
+            Self::Back => "Back",
+            Self::Forward => "Forward",
+            Self::Other(_) => "Other",
         }
     }
 }
 
+/// Counts the presses of a mouse button that make up a multi-click.
+#[derive(Default)]
+struct ClickCounter {
+    /// The button and time in milliseconds of the last press, and the position of the first press of the multi-click.
+    last_press: Option<(MouseButton, u32, (i16, i16))>,
+    count: u32,
+}
+
+impl ClickCounter {
+    /// Returns the click count of a press of `button` at `time` and `position`. The press continues the clicks of the
+    /// last press if it is at most `max_time` milliseconds later, and at most `max_distance` pixels away on each axis
+    /// from the first press of the clicks.
+    fn press(
+        &mut self,
+        button: MouseButton,
+        time: u32,
+        position: (i16, i16),
+        max_time: u32,
+        max_distance: (i32, i32),
+    ) -> u32 {
+        let first_position = self.last_press.and_then(|(last_button, last_time, first_position)| {
+            let continues_clicks = last_button == button
+                // The timestamps wrap around after 49.7 days.
+                && time.wrapping_sub(last_time) <= max_time
+                && (position.0 as i32 - first_position.0 as i32).abs() <= max_distance.0
+                && (position.1 as i32 - first_position.1 as i32).abs() <= max_distance.1;
+
+            continues_clicks.then_some(first_position)
+        });
+
+        self.count = if first_position.is_some() { self.count + 1 } else { 1 };
+        self.last_press = Some((button, time, first_position.unwrap_or(position)));
+
+        self.count
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Keys {
     Backspace,
     Enter,
     Tab,
+    Clear,
     Shift,
     Control,
 
@@ -729,6 +4885,17 @@ pub enum Keys {
     Delete,
     Help,
 
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+
     A,
     B,
     C,
@@ -812,24 +4979,101 @@ pub enum Keys {
     RShift,
     LControl,
     RControl,
+    /// The left Alt key.
     LMenu,
+    /// The right Alt key, or AltGr.
     RMenu,
 
+    BrowserBack,
+    BrowserForward,
+    BrowserRefresh,
+    BrowserStop,
+    BrowserSearch,
+    BrowserFavorites,
+    BrowserHome,
+
+    VolumeMute,
+    VolumeDown,
+    VolumeUp,
+    MediaNextTrack,
+    MediaPrevTrack,
+    MediaStop,
+    MediaPlayPause,
+    LaunchMail,
+    LaunchMediaSelect,
+    LaunchApp1,
+    LaunchApp2,
+
+    /// The `;:` key on a US keyboard.
     Semicolon,
+    /// The `=+` key on a US keyboard.
     Plus,
     Comma,
     Minus,
     Period,
+    /// The `/?` key on a US keyboard.
     Slash,
+    /// The `` `~ `` key on a US keyboard.
     Grave,
+
+    /// The `/?` key of Brazilian ABNT keyboards.
+    AbntC1,
+    /// The keypad `.` key of Brazilian ABNT keyboards.
+    AbntC2,
+    /// The `[{` key on a US keyboard.
+    LeftBracket,
+    /// The `\\|` key on a US keyboard.
+    Backslash,
+    /// The `]}` key on a US keyboard.
+    RightBracket,
+    /// The `'"` key on a US keyboard.
+    Apostrophe,
+    /// A layout specific key, such as the `!§` key on a French keyboard.
+    Oem8,
+    /// The extra `<>` key next to the left Shift key of ISO keyboards.
+    Oem102,
 }
 
 impl Keys {
+    /// Every key.
+    pub const ALL: &'static [Keys] = &[
+        Self::Backspace, Self::Enter, Self::Tab, Self::Clear, Self::Shift, Self::Control, Self::Pause, Self::Capital,
+        Self::Escape, Self::Convert, Self::Nonconvert, Self::Accept, Self::Modechange, Self::Space, Self::Prior,
+        Self::Next, Self::End, Self::Home, Self::Left, Self::Up, Self::Right, Self::Down, Self::Select, Self::Print,
+        Self::Execute, Self::Snapshot, Self::Insert, Self::Delete, Self::Help, Self::Key0, Self::Key1, Self::Key2,
+        Self::Key3, Self::Key4, Self::Key5, Self::Key6, Self::Key7, Self::Key8, Self::Key9, Self::A, Self::B, Self::C,
+        Self::D, Self::E, Self::F, Self::G, Self::H, Self::I, Self::J, Self::K, Self::L, Self::M, Self::N, Self::O,
+        Self::P, Self::Q, Self::R, Self::S, Self::T, Self::U, Self::V, Self::W, Self::X, Self::Y, Self::Z, Self::LWin,
+        Self::RWin, Self::Apps, Self::Sleep, Self::Numpad0, Self::Numpad1, Self::Numpad2, Self::Numpad3, Self::Numpad4,
+        Self::Numpad5, Self::Numpad6, Self::Numpad7, Self::Numpad8, Self::Numpad9, Self::Multiply, Self::Add,
+        Self::Separator, Self::Subtract, Self::Decimal, Self::Divide, Self::F1, Self::F2, Self::F3, Self::F4, Self::F5,
+        Self::F6, Self::F7, Self::F8, Self::F9, Self::F10, Self::F11, Self::F12, Self::F13, Self::F14, Self::F15,
+        Self::F16, Self::F17, Self::F18, Self::F19, Self::F20, Self::F21, Self::F22, Self::F23, Self::F24,
+        Self::Numlock, Self::Scroll, Self::NumpadEqual, Self::LShift, Self::RShift, Self::LControl, Self::RControl,
+        Self::LMenu, Self::RMenu, Self::BrowserBack, Self::BrowserForward, Self::BrowserRefresh, Self::BrowserStop,
+        Self::BrowserSearch, Self::BrowserFavorites, Self::BrowserHome, Self::VolumeMute, Self::VolumeDown,
+        Self::VolumeUp, Self::MediaNextTrack, Self::MediaPrevTrack, Self::MediaStop, Self::MediaPlayPause,
+        Self::LaunchMail, Self::LaunchMediaSelect, Self::LaunchApp1, Self::LaunchApp2, Self::Semicolon, Self::Plus,
+        Self::Comma, Self::Minus, Self::Period, Self::Slash, Self::Grave, Self::AbntC1, Self::AbntC2, Self::LeftBracket,
+        Self::Backslash, Self::RightBracket, Self::Apostrophe, Self::Oem8, Self::Oem102,
+    ];
+
+    /// Returns the key with the Windows virtual key code `s`.
+    ///
+    /// # Panics
+    /// Panics if `s` is not a virtual key code of a key. See [`Keys::try_from_usize`] for a non-panicking version.
+    #[deprecated(note = "panics on virtual key codes without a key, use `Keys::try_from_usize` instead")]
     pub fn from_usize(s: usize) -> Self {
-        match s {
+        Self::try_from_usize(s).expect("Provided usize does not correspond to a valid Key.")
+    }
+
+    /// Returns the key with the Windows virtual key code `s`, or `None` if there is no such key.
+    pub fn try_from_usize(s: usize) -> Option<Self> {
+        let key = match s {
             0x08 => Self::Backspace,
             0x0D => Self::Enter,
             0x09 => Self::Tab,
+            0x0C => Self::Clear,
             0x10 => Self::Shift,
             0x11 => Self::Control,
 
@@ -860,6 +5104,17 @@ impl Keys {
             0x2E => Self::Delete,
             0x2F => Self::Help,
 
+            0x30 => Self::Key0,
+            0x31 => Self::Key1,
+            0x32 => Self::Key2,
+            0x33 => Self::Key3,
+            0x34 => Self::Key4,
+            0x35 => Self::Key5,
+            0x36 => Self::Key6,
+            0x37 => Self::Key7,
+            0x38 => Self::Key8,
+            0x39 => Self::Key9,
+
             0x41 => Self::A,
             0x42 => Self::B,
             0x43 => Self::C,
@@ -946,6 +5201,26 @@ impl Keys {
             0xA4 => Self::LMenu,
             0xA5 => Self::RMenu,
 
+            0xA6 => Self::BrowserBack,
+            0xA7 => Self::BrowserForward,
+            0xA8 => Self::BrowserRefresh,
+            0xA9 => Self::BrowserStop,
+            0xAA => Self::BrowserSearch,
+            0xAB => Self::BrowserFavorites,
+            0xAC => Self::BrowserHome,
+
+            0xAD => Self::VolumeMute,
+            0xAE => Self::VolumeDown,
+            0xAF => Self::VolumeUp,
+            0xB0 => Self::MediaNextTrack,
+            0xB1 => Self::MediaPrevTrack,
+            0xB2 => Self::MediaStop,
+            0xB3 => Self::MediaPlayPause,
+            0xB4 => Self::LaunchMail,
+            0xB5 => Self::LaunchMediaSelect,
+            0xB6 => Self::LaunchApp1,
+            0xB7 => Self::LaunchApp2,
+
             0xBA => Self::Semicolon,
             0xBB => Self::Plus,
             0xBC => Self::Comma,
@@ -953,15 +5228,544 @@ impl Keys {
             0xBE => Self::Period,
             0xBF => Self::Slash,
             0xC0 => Self::Grave,
-            _ => panic!("Provided usize does not corrospond to a valid Key."),
+
+            0xC1 => Self::AbntC1,
+            0xC2 => Self::AbntC2,
+            0xDB => Self::LeftBracket,
+            0xDC => Self::Backslash,
+            0xDD => Self::RightBracket,
+            0xDE => Self::Apostrophe,
+            0xDF => Self::Oem8,
+            0xE2 => Self::Oem102,
+            _ => return None,
+        };
+
+        Some(key)
+    }
+
+    /// Returns the Windows virtual key code of the key.
+    pub fn as_usize(&self) -> usize {
+        match self {
+            Self::Backspace => 0x08,
+            Self::Enter => 0x0D,
+            Self::Tab => 0x09,
+            Self::Clear => 0x0C,
+            Self::Shift => 0x10,
+            Self::Control => 0x11,
+
+            Self::Pause => 0x13,
+            Self::Capital => 0x14,
+
+            Self::Escape => 0x1B,
+
+            Self::Convert => 0x1C,
+            Self::Nonconvert => 0x1D,
+            Self::Accept => 0x1E,
+            Self::Modechange => 0x1F,
+
+            Self::Space => 0x20,
+            Self::Prior => 0x21,
+            Self::Next => 0x22,
+            Self::End => 0x23,
+            Self::Home => 0x24,
+            Self::Left => 0x25,
+            Self::Up => 0x26,
+            Self::Right => 0x27,
+            Self::Down => 0x28,
+            Self::Select => 0x29,
+            Self::Print => 0x2A,
+            Self::Execute => 0x2B,
+            Self::Snapshot => 0x2C,
+            Self::Insert => 0x2D,
+            Self::Delete => 0x2E,
+            Self::Help => 0x2F,
+
+            Self::Key0 => 0x30,
+            Self::Key1 => 0x31,
+            Self::Key2 => 0x32,
+            Self::Key3 => 0x33,
+            Self::Key4 => 0x34,
+            Self::Key5 => 0x35,
+            Self::Key6 => 0x36,
+            Self::Key7 => 0x37,
+            Self::Key8 => 0x38,
+            Self::Key9 => 0x39,
+
+            Self::A => 0x41,
+            Self::B => 0x42,
+            Self::C => 0x43,
+            Self::D => 0x44,
+            Self::E => 0x45,
+            Self::F => 0x46,
+            Self::G => 0x47,
+            Self::H => 0x48,
+            Self::I => 0x49,
+            Self::J => 0x4A,
+            Self::K => 0x4B,
+            Self::L => 0x4C,
+            Self::M => 0x4D,
+            Self::N => 0x4E,
+            Self::O => 0x4F,
+            Self::P => 0x50,
+            Self::Q => 0x51,
+            Self::R => 0x52,
+            Self::S => 0x53,
+            Self::T => 0x54,
+            Self::U => 0x55,
+            Self::V => 0x56,
+            Self::W => 0x57,
+            Self::X => 0x58,
+            Self::Y => 0x59,
+            Self::Z => 0x5A,
+
+            Self::LWin => 0x5B,
+            Self::RWin => 0x5C,
+            Self::Apps => 0x5D,
+
+            Self::Sleep => 0x5F,
+
+            Self::Numpad0 => 0x60,
+            Self::Numpad1 => 0x61,
+            Self::Numpad2 => 0x62,
+            Self::Numpad3 => 0x63,
+            Self::Numpad4 => 0x64,
+            Self::Numpad5 => 0x65,
+            Self::Numpad6 => 0x66,
+            Self::Numpad7 => 0x67,
+            Self::Numpad8 => 0x68,
+            Self::Numpad9 => 0x69,
+            Self::Multiply => 0x6A,
+            Self::Add => 0x6B,
+            Self::Separator => 0x6C,
+            Self::Subtract => 0x6D,
+            Self::Decimal => 0x6E,
+            Self::Divide => 0x6F,
+            Self::F1 => 0x70,
+            Self::F2 => 0x71,
+            Self::F3 => 0x72,
+            Self::F4 => 0x73,
+            Self::F5 => 0x74,
+            Self::F6 => 0x75,
+            Self::F7 => 0x76,
+            Self::F8 => 0x77,
+            Self::F9 => 0x78,
+            Self::F10 => 0x79,
+            Self::F11 => 0x7A,
+            Self::F12 => 0x7B,
+            Self::F13 => 0x7C,
+            Self::F14 => 0x7D,
+            Self::F15 => 0x7E,
+            Self::F16 => 0x7F,
+            Self::F17 => 0x80,
+            Self::F18 => 0x81,
+            Self::F19 => 0x82,
+            Self::F20 => 0x83,
+            Self::F21 => 0x84,
+            Self::F22 => 0x85,
+            Self::F23 => 0x86,
+            Self::F24 => 0x87,
+
+            Self::Numlock => 0x90,
+            Self::Scroll => 0x91,
+
+            Self::NumpadEqual => 0x92,
+
+            Self::LShift => 0xA0,
+            Self::RShift => 0xA1,
+            Self::LControl => 0xA2,
+            Self::RControl => 0xA3,
+            Self::LMenu => 0xA4,
+            Self::RMenu => 0xA5,
+
+            Self::BrowserBack => 0xA6,
+            Self::BrowserForward => 0xA7,
+            Self::BrowserRefresh => 0xA8,
+            Self::BrowserStop => 0xA9,
+            Self::BrowserSearch => 0xAA,
+            Self::BrowserFavorites => 0xAB,
+            Self::BrowserHome => 0xAC,
+
+            Self::VolumeMute => 0xAD,
+            Self::VolumeDown => 0xAE,
+            Self::VolumeUp => 0xAF,
+            Self::MediaNextTrack => 0xB0,
+            Self::MediaPrevTrack => 0xB1,
+            Self::MediaStop => 0xB2,
+            Self::MediaPlayPause => 0xB3,
+            Self::LaunchMail => 0xB4,
+            Self::LaunchMediaSelect => 0xB5,
+            Self::LaunchApp1 => 0xB6,
+            Self::LaunchApp2 => 0xB7,
+
+            Self::Semicolon => 0xBA,
+            Self::Plus => 0xBB,
+            Self::Comma => 0xBC,
+            Self::Minus => 0xBD,
+            Self::Period => 0xBE,
+            Self::Slash => 0xBF,
+            Self::Grave => 0xC0,
+
+            Self::AbntC1 => 0xC1,
+            Self::AbntC2 => 0xC2,
+            Self::LeftBracket => 0xDB,
+            Self::Backslash => 0xDC,
+            Self::RightBracket => 0xDD,
+            Self::Apostrophe => 0xDE,
+            Self::Oem8 => 0xDF,
+            Self::Oem102 => 0xE2,
         }
     }
 
+    /// Returns the key producing the X key symbol `key_sym`, or `None` if there is no such key.
+    ///
+    /// Keypad navigation symbols, such as `KP_Home`, map to the navigation keys they stand for.
+    #[cfg(target_os = "linux")]
+    pub fn from_keysym(key_sym: u32) -> Option<Self> {
+        let key = match key_sym {
+            x11::keysym::XK_BackSpace => Self::Backspace,
+            x11::keysym::XK_Return | x11::keysym::XK_KP_Enter => Self::Enter,
+            x11::keysym::XK_Tab => Self::Tab,
+            x11::keysym::XK_Clear | x11::keysym::XK_KP_Begin => Self::Clear,
+
+            x11::keysym::XK_Pause => Self::Pause,
+            x11::keysym::XK_Caps_Lock => Self::Capital,
+
+            x11::keysym::XK_Escape => Self::Escape,
+
+            x11::keysym::XK_Henkan => Self::Convert,
+            x11::keysym::XK_Muhenkan => Self::Nonconvert,
+            x11::keysym::XK_Mode_switch => Self::Modechange,
+
+            x11::keysym::XK_space => Self::Space,
+            x11::keysym::XK_Prior | x11::keysym::XK_KP_Prior => Self::Prior,
+            x11::keysym::XK_Next | x11::keysym::XK_KP_Next => Self::Next,
+            x11::keysym::XK_End | x11::keysym::XK_KP_End => Self::End,
+            x11::keysym::XK_Home | x11::keysym::XK_KP_Home => Self::Home,
+            x11::keysym::XK_Left | x11::keysym::XK_KP_Left => Self::Left,
+            x11::keysym::XK_Up | x11::keysym::XK_KP_Up => Self::Up,
+            x11::keysym::XK_Right | x11::keysym::XK_KP_Right => Self::Right,
+            x11::keysym::XK_Down | x11::keysym::XK_KP_Down => Self::Down,
+            x11::keysym::XK_Select => Self::Select,
+            x11::keysym::XK_Print => Self::Print,
+            x11::keysym::XK_Execute => Self::Execute,
+            x11::keysym::XK_Insert | x11::keysym::XK_KP_Insert => Self::Insert,
+            x11::keysym::XK_Delete | x11::keysym::XK_KP_Delete => Self::Delete,
+            x11::keysym::XK_Help => Self::Help,
+
+            x11::keysym::XK_0 => Self::Key0,
+            x11::keysym::XK_1 => Self::Key1,
+            x11::keysym::XK_2 => Self::Key2,
+            x11::keysym::XK_3 => Self::Key3,
+            x11::keysym::XK_4 => Self::Key4,
+            x11::keysym::XK_5 => Self::Key5,
+            x11::keysym::XK_6 => Self::Key6,
+            x11::keysym::XK_7 => Self::Key7,
+            x11::keysym::XK_8 => Self::Key8,
+            x11::keysym::XK_9 => Self::Key9,
+
+            x11::keysym::XK_a | x11::keysym::XK_A => Self::A,
+            x11::keysym::XK_b | x11::keysym::XK_B => Self::B,
+            x11::keysym::XK_c | x11::keysym::XK_C => Self::C,
+            x11::keysym::XK_d | x11::keysym::XK_D => Self::D,
+            x11::keysym::XK_e | x11::keysym::XK_E => Self::E,
+            x11::keysym::XK_f | x11::keysym::XK_F => Self::F,
+            x11::keysym::XK_g | x11::keysym::XK_G => Self::G,
+            x11::keysym::XK_h | x11::keysym::XK_H => Self::H,
+            x11::keysym::XK_i | x11::keysym::XK_I => Self::I,
+            x11::keysym::XK_j | x11::keysym::XK_J => Self::J,
+            x11::keysym::XK_k | x11::keysym::XK_K => Self::K,
+            x11::keysym::XK_l | x11::keysym::XK_L => Self::L,
+            x11::keysym::XK_m | x11::keysym::XK_M => Self::M,
+            x11::keysym::XK_n | x11::keysym::XK_N => Self::N,
+            x11::keysym::XK_o | x11::keysym::XK_O => Self::O,
+            x11::keysym::XK_p | x11::keysym::XK_P => Self::P,
+            x11::keysym::XK_q | x11::keysym::XK_Q => Self::Q,
+            x11::keysym::XK_r | x11::keysym::XK_R => Self::R,
+            x11::keysym::XK_s | x11::keysym::XK_S => Self::S,
+            x11::keysym::XK_t | x11::keysym::XK_T => Self::T,
+            x11::keysym::XK_u | x11::keysym::XK_U => Self::U,
+            x11::keysym::XK_v | x11::keysym::XK_V => Self::V,
+            x11::keysym::XK_w | x11::keysym::XK_W => Self::W,
+            x11::keysym::XK_x | x11::keysym::XK_X => Self::X,
+            x11::keysym::XK_y | x11::keysym::XK_Y => Self::Y,
+            x11::keysym::XK_z | x11::keysym::XK_Z => Self::Z,
+
+            x11::keysym::XK_Super_L => Self::LWin,
+            x11::keysym::XK_Super_R => Self::RWin,
+            x11::keysym::XK_Menu => Self::Apps,
+
+            x11::keysym::XF86XK_Sleep => Self::Sleep,
+
+            x11::keysym::XK_KP_0 => Self::Numpad0,
+            x11::keysym::XK_KP_1 => Self::Numpad1,
+            x11::keysym::XK_KP_2 => Self::Numpad2,
+            x11::keysym::XK_KP_3 => Self::Numpad3,
+            x11::keysym::XK_KP_4 => Self::Numpad4,
+            x11::keysym::XK_KP_5 => Self::Numpad5,
+            x11::keysym::XK_KP_6 => Self::Numpad6,
+            x11::keysym::XK_KP_7 => Self::Numpad7,
+            x11::keysym::XK_KP_8 => Self::Numpad8,
+            x11::keysym::XK_KP_9 => Self::Numpad9,
+            x11::keysym::XK_KP_Multiply => Self::Multiply,
+            x11::keysym::XK_KP_Add => Self::Add,
+            x11::keysym::XK_KP_Separator => Self::Separator,
+            x11::keysym::XK_KP_Subtract => Self::Subtract,
+            x11::keysym::XK_KP_Decimal => Self::Decimal,
+            x11::keysym::XK_KP_Divide => Self::Divide,
+            x11::keysym::XK_F1 => Self::F1,
+            x11::keysym::XK_F2 => Self::F2,
+            x11::keysym::XK_F3 => Self::F3,
+            x11::keysym::XK_F4 => Self::F4,
+            x11::keysym::XK_F5 => Self::F5,
+            x11::keysym::XK_F6 => Self::F6,
+            x11::keysym::XK_F7 => Self::F7,
+            x11::keysym::XK_F8 => Self::F8,
+            x11::keysym::XK_F9 => Self::F9,
+            x11::keysym::XK_F10 => Self::F10,
+            x11::keysym::XK_F11 => Self::F11,
+            x11::keysym::XK_F12 => Self::F12,
+            x11::keysym::XK_F13 => Self::F13,
+            x11::keysym::XK_F14 => Self::F14,
+            x11::keysym::XK_F15 => Self::F15,
+            x11::keysym::XK_F16 => Self::F16,
+            x11::keysym::XK_F17 => Self::F17,
+            x11::keysym::XK_F18 => Self::F18,
+            x11::keysym::XK_F19 => Self::F19,
+            x11::keysym::XK_F20 => Self::F20,
+            x11::keysym::XK_F21 => Self::F21,
+            x11::keysym::XK_F22 => Self::F22,
+            x11::keysym::XK_F23 => Self::F23,
+            x11::keysym::XK_F24 => Self::F24,
+
+            x11::keysym::XK_Num_Lock => Self::Numlock,
+            x11::keysym::XK_Scroll_Lock => Self::Scroll,
+
+            x11::keysym::XK_KP_Equal => Self::NumpadEqual,
+
+            x11::keysym::XK_Shift_L => Self::LShift,
+            x11::keysym::XK_Shift_R => Self::RShift,
+            x11::keysym::XK_Control_L => Self::LControl,
+            x11::keysym::XK_Control_R => Self::RControl,
+            x11::keysym::XK_Alt_L | x11::keysym::XK_Meta_L => Self::LMenu,
+            x11::keysym::XK_Alt_R | x11::keysym::XK_Meta_R | x11::keysym::XK_ISO_Level3_Shift => Self::RMenu,
+
+            x11::keysym::XF86XK_Back => Self::BrowserBack,
+            x11::keysym::XF86XK_Forward => Self::BrowserForward,
+            x11::keysym::XF86XK_Refresh => Self::BrowserRefresh,
+            x11::keysym::XF86XK_Stop => Self::BrowserStop,
+            x11::keysym::XF86XK_Search => Self::BrowserSearch,
+            x11::keysym::XF86XK_Favorites => Self::BrowserFavorites,
+            x11::keysym::XF86XK_HomePage => Self::BrowserHome,
+
+            x11::keysym::XF86XK_AudioMute => Self::VolumeMute,
+            x11::keysym::XF86XK_AudioLowerVolume => Self::VolumeDown,
+            x11::keysym::XF86XK_AudioRaiseVolume => Self::VolumeUp,
+            x11::keysym::XF86XK_AudioNext => Self::MediaNextTrack,
+            x11::keysym::XF86XK_AudioPrev => Self::MediaPrevTrack,
+            x11::keysym::XF86XK_AudioStop => Self::MediaStop,
+            x11::keysym::XF86XK_AudioPlay | x11::keysym::XF86XK_AudioPause => Self::MediaPlayPause,
+            x11::keysym::XF86XK_Mail => Self::LaunchMail,
+            x11::keysym::XF86XK_AudioMedia => Self::LaunchMediaSelect,
+            x11::keysym::XF86XK_MyComputer => Self::LaunchApp1,
+            x11::keysym::XF86XK_Calculator => Self::LaunchApp2,
+
+            x11::keysym::XK_semicolon => Self::Semicolon,
+            x11::keysym::XK_plus | x11::keysym::XK_equal => Self::Plus,
+            x11::keysym::XK_comma => Self::Comma,
+            x11::keysym::XK_minus => Self::Minus,
+            x11::keysym::XK_period => Self::Period,
+            x11::keysym::XK_slash => Self::Slash,
+            x11::keysym::XK_grave => Self::Grave,
+
+            x11::keysym::XK_bracketleft => Self::LeftBracket,
+            x11::keysym::XK_backslash => Self::Backslash,
+            x11::keysym::XK_bracketright => Self::RightBracket,
+            x11::keysym::XK_apostrophe => Self::Apostrophe,
+            x11::keysym::XK_less => Self::Oem102,
+            _ => return None,
+        };
+
+        Some(key)
+    }
+
+    /// Returns the X key symbol of the key on a US layout, or `None` for keys without one, such as the Windows only
+    /// [`Keys::Shift`] and [`Keys::Control`].
+    #[cfg(target_os = "linux")]
+    pub fn as_keysym(&self) -> Option<u32> {
+        let key_sym = match self {
+            Self::Backspace => x11::keysym::XK_BackSpace,
+            Self::Enter => x11::keysym::XK_Return,
+            Self::Tab => x11::keysym::XK_Tab,
+            Self::Clear => x11::keysym::XK_Clear,
+
+            Self::Pause => x11::keysym::XK_Pause,
+            Self::Capital => x11::keysym::XK_Caps_Lock,
+
+            Self::Escape => x11::keysym::XK_Escape,
+
+            Self::Convert => x11::keysym::XK_Henkan,
+            Self::Nonconvert => x11::keysym::XK_Muhenkan,
+            Self::Modechange => x11::keysym::XK_Mode_switch,
+
+            Self::Space => x11::keysym::XK_space,
+            Self::Prior => x11::keysym::XK_Prior,
+            Self::Next => x11::keysym::XK_Next,
+            Self::End => x11::keysym::XK_End,
+            Self::Home => x11::keysym::XK_Home,
+            Self::Left => x11::keysym::XK_Left,
+            Self::Up => x11::keysym::XK_Up,
+            Self::Right => x11::keysym::XK_Right,
+            Self::Down => x11::keysym::XK_Down,
+            Self::Select => x11::keysym::XK_Select,
+            Self::Print => x11::keysym::XK_Print,
+            Self::Execute => x11::keysym::XK_Execute,
+            Self::Insert => x11::keysym::XK_Insert,
+            Self::Delete => x11::keysym::XK_Delete,
+            Self::Help => x11::keysym::XK_Help,
+
+            Self::Key0 => x11::keysym::XK_0,
+            Self::Key1 => x11::keysym::XK_1,
+            Self::Key2 => x11::keysym::XK_2,
+            Self::Key3 => x11::keysym::XK_3,
+            Self::Key4 => x11::keysym::XK_4,
+            Self::Key5 => x11::keysym::XK_5,
+            Self::Key6 => x11::keysym::XK_6,
+            Self::Key7 => x11::keysym::XK_7,
+            Self::Key8 => x11::keysym::XK_8,
+            Self::Key9 => x11::keysym::XK_9,
+
+            Self::A => x11::keysym::XK_a,
+            Self::B => x11::keysym::XK_b,
+            Self::C => x11::keysym::XK_c,
+            Self::D => x11::keysym::XK_d,
+            Self::E => x11::keysym::XK_e,
+            Self::F => x11::keysym::XK_f,
+            Self::G => x11::keysym::XK_g,
+            Self::H => x11::keysym::XK_h,
+            Self::I => x11::keysym::XK_i,
+            Self::J => x11::keysym::XK_j,
+            Self::K => x11::keysym::XK_k,
+            Self::L => x11::keysym::XK_l,
+            Self::M => x11::keysym::XK_m,
+            Self::N => x11::keysym::XK_n,
+            Self::O => x11::keysym::XK_o,
+            Self::P => x11::keysym::XK_p,
+            Self::Q => x11::keysym::XK_q,
+            Self::R => x11::keysym::XK_r,
+            Self::S => x11::keysym::XK_s,
+            Self::T => x11::keysym::XK_t,
+            Self::U => x11::keysym::XK_u,
+            Self::V => x11::keysym::XK_v,
+            Self::W => x11::keysym::XK_w,
+            Self::X => x11::keysym::XK_x,
+            Self::Y => x11::keysym::XK_y,
+            Self::Z => x11::keysym::XK_z,
+
+            Self::LWin => x11::keysym::XK_Super_L,
+            Self::RWin => x11::keysym::XK_Super_R,
+            Self::Apps => x11::keysym::XK_Menu,
+
+            Self::Sleep => x11::keysym::XF86XK_Sleep,
+
+            Self::Numpad0 => x11::keysym::XK_KP_0,
+            Self::Numpad1 => x11::keysym::XK_KP_1,
+            Self::Numpad2 => x11::keysym::XK_KP_2,
+            Self::Numpad3 => x11::keysym::XK_KP_3,
+            Self::Numpad4 => x11::keysym::XK_KP_4,
+            Self::Numpad5 => x11::keysym::XK_KP_5,
+            Self::Numpad6 => x11::keysym::XK_KP_6,
+            Self::Numpad7 => x11::keysym::XK_KP_7,
+            Self::Numpad8 => x11::keysym::XK_KP_8,
+            Self::Numpad9 => x11::keysym::XK_KP_9,
+            Self::Multiply => x11::keysym::XK_KP_Multiply,
+            Self::Add => x11::keysym::XK_KP_Add,
+            Self::Separator => x11::keysym::XK_KP_Separator,
+            Self::Subtract => x11::keysym::XK_KP_Subtract,
+            Self::Decimal => x11::keysym::XK_KP_Decimal,
+            Self::Divide => x11::keysym::XK_KP_Divide,
+            Self::F1 => x11::keysym::XK_F1,
+            Self::F2 => x11::keysym::XK_F2,
+            Self::F3 => x11::keysym::XK_F3,
+            Self::F4 => x11::keysym::XK_F4,
+            Self::F5 => x11::keysym::XK_F5,
+            Self::F6 => x11::keysym::XK_F6,
+            Self::F7 => x11::keysym::XK_F7,
+            Self::F8 => x11::keysym::XK_F8,
+            Self::F9 => x11::keysym::XK_F9,
+            Self::F10 => x11::keysym::XK_F10,
+            Self::F11 => x11::keysym::XK_F11,
+            Self::F12 => x11::keysym::XK_F12,
+            Self::F13 => x11::keysym::XK_F13,
+            Self::F14 => x11::keysym::XK_F14,
+            Self::F15 => x11::keysym::XK_F15,
+            Self::F16 => x11::keysym::XK_F16,
+            Self::F17 => x11::keysym::XK_F17,
+            Self::F18 => x11::keysym::XK_F18,
+            Self::F19 => x11::keysym::XK_F19,
+            Self::F20 => x11::keysym::XK_F20,
+            Self::F21 => x11::keysym::XK_F21,
+            Self::F22 => x11::keysym::XK_F22,
+            Self::F23 => x11::keysym::XK_F23,
+            Self::F24 => x11::keysym::XK_F24,
+
+            Self::Numlock => x11::keysym::XK_Num_Lock,
+            Self::Scroll => x11::keysym::XK_Scroll_Lock,
+
+            Self::NumpadEqual => x11::keysym::XK_KP_Equal,
+
+            Self::LShift => x11::keysym::XK_Shift_L,
+            Self::RShift => x11::keysym::XK_Shift_R,
+            Self::LControl => x11::keysym::XK_Control_L,
+            Self::RControl => x11::keysym::XK_Control_R,
+            Self::LMenu => x11::keysym::XK_Alt_L,
+            Self::RMenu => x11::keysym::XK_Alt_R,
+
+            Self::BrowserBack => x11::keysym::XF86XK_Back,
+            Self::BrowserForward => x11::keysym::XF86XK_Forward,
+            Self::BrowserRefresh => x11::keysym::XF86XK_Refresh,
+            Self::BrowserStop => x11::keysym::XF86XK_Stop,
+            Self::BrowserSearch => x11::keysym::XF86XK_Search,
+            Self::BrowserFavorites => x11::keysym::XF86XK_Favorites,
+            Self::BrowserHome => x11::keysym::XF86XK_HomePage,
+
+            Self::VolumeMute => x11::keysym::XF86XK_AudioMute,
+            Self::VolumeDown => x11::keysym::XF86XK_AudioLowerVolume,
+            Self::VolumeUp => x11::keysym::XF86XK_AudioRaiseVolume,
+            Self::MediaNextTrack => x11::keysym::XF86XK_AudioNext,
+            Self::MediaPrevTrack => x11::keysym::XF86XK_AudioPrev,
+            Self::MediaStop => x11::keysym::XF86XK_AudioStop,
+            Self::MediaPlayPause => x11::keysym::XF86XK_AudioPlay,
+            Self::LaunchMail => x11::keysym::XF86XK_Mail,
+            Self::LaunchMediaSelect => x11::keysym::XF86XK_AudioMedia,
+            Self::LaunchApp1 => x11::keysym::XF86XK_MyComputer,
+            Self::LaunchApp2 => x11::keysym::XF86XK_Calculator,
+
+            Self::Semicolon => x11::keysym::XK_semicolon,
+            Self::Plus => x11::keysym::XK_plus,
+            Self::Comma => x11::keysym::XK_comma,
+            Self::Minus => x11::keysym::XK_minus,
+            Self::Period => x11::keysym::XK_period,
+            Self::Slash => x11::keysym::XK_slash,
+            Self::Grave => x11::keysym::XK_grave,
+
+            Self::LeftBracket => x11::keysym::XK_bracketleft,
+            Self::Backslash => x11::keysym::XK_backslash,
+            Self::RightBracket => x11::keysym::XK_bracketright,
+            Self::Apostrophe => x11::keysym::XK_apostrophe,
+            Self::Oem102 => x11::keysym::XK_less,
+            Self::Shift | Self::Control | Self::Accept | Self::Snapshot
+                | Self::AbntC1 | Self::AbntC2 | Self::Oem8 => return None,
+        };
+
+        Some(key_sym)
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             Self::Backspace => "Backspace",
             Self::Enter => "Enter",
             Self::Tab => "Tab",
+            Self::Clear => "Clear",
             Self::Shift => "Shift",
             Self::Control => "Control",
 
@@ -992,6 +5796,17 @@ impl Keys {
             Self::Delete => "Delete",
             Self::Help => "Help",
 
+            Self::Key0 => "Key0",
+            Self::Key1 => "Key1",
+            Self::Key2 => "Key2",
+            Self::Key3 => "Key3",
+            Self::Key4 => "Key4",
+            Self::Key5 => "Key5",
+            Self::Key6 => "Key6",
+            Self::Key7 => "Key7",
+            Self::Key8 => "Key8",
+            Self::Key9 => "Key9",
+
             Self::A => "A",
             Self::B => "B",
             Self::C => "C",
@@ -1078,6 +5893,26 @@ impl Keys {
             Self::LMenu => "LMenu",
             Self::RMenu => "RMenu",
 
+            Self::BrowserBack => "BrowserBack",
+            Self::BrowserForward => "BrowserForward",
+            Self::BrowserRefresh => "BrowserRefresh",
+            Self::BrowserStop => "BrowserStop",
+            Self::BrowserSearch => "BrowserSearch",
+            Self::BrowserFavorites => "BrowserFavorites",
+            Self::BrowserHome => "BrowserHome",
+
+            Self::VolumeMute => "VolumeMute",
+            Self::VolumeDown => "VolumeDown",
+            Self::VolumeUp => "VolumeUp",
+            Self::MediaNextTrack => "MediaNextTrack",
+            Self::MediaPrevTrack => "MediaPrevTrack",
+            Self::MediaStop => "MediaStop",
+            Self::MediaPlayPause => "MediaPlayPause",
+            Self::LaunchMail => "LaunchMail",
+            Self::LaunchMediaSelect => "LaunchMediaSelect",
+            Self::LaunchApp1 => "LaunchApp1",
+            Self::LaunchApp2 => "LaunchApp2",
+
             Self::Semicolon => "Semicolon",
             Self::Plus => "Plus",
             Self::Comma => "Comma",
@@ -1085,7 +5920,66 @@ impl Keys {
             Self::Period => "Period",
             Self::Slash => "Slash",
             Self::Grave => "Grave",
+
+            Self::AbntC1 => "AbntC1",
+            Self::AbntC2 => "AbntC2",
+            Self::LeftBracket => "LeftBracket",
+            Self::Backslash => "Backslash",
+            Self::RightBracket => "RightBracket",
+            Self::Apostrophe => "Apostrophe",
+            Self::Oem8 => "Oem8",
+            Self::Oem102 => "Oem102",
         }
     }
 }
- 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_TIME: u32 = 500;
+    const MAX_DISTANCE: (i32, i32) = (2, 2);
+
+    fn press(counter: &mut ClickCounter, button: MouseButton, time: u32, position: (i16, i16)) -> u32 {
+        counter.press(button, time, position, MAX_TIME, MAX_DISTANCE)
+    }
+
+    #[test]
+    fn click_counter_counts_presses_within_time_limit() {
+        let mut counter = ClickCounter::default();
+
+        assert_eq!(press(&mut counter, MouseButton::Left, 1000, (10, 10)), 1);
+        assert_eq!(press(&mut counter, MouseButton::Left, 1400, (10, 10)), 2);
+        assert_eq!(press(&mut counter, MouseButton::Left, 1900, (10, 10)), 3);
+        assert_eq!(press(&mut counter, MouseButton::Left, 2401, (10, 10)), 1);
+    }
+
+    #[test]
+    fn click_counter_measures_distance_from_first_press() {
+        let mut counter = ClickCounter::default();
+
+        assert_eq!(press(&mut counter, MouseButton::Left, 0, (10, 10)), 1);
+        assert_eq!(press(&mut counter, MouseButton::Left, 100, (12, 8)), 2);
+        // Within reach of the last press, but not of the first.
+        assert_eq!(press(&mut counter, MouseButton::Left, 200, (13, 8)), 1);
+        assert_eq!(press(&mut counter, MouseButton::Left, 300, (11, 10)), 2);
+    }
+
+    #[test]
+    fn click_counter_restarts_on_button_change() {
+        let mut counter = ClickCounter::default();
+
+        assert_eq!(press(&mut counter, MouseButton::Left, 0, (10, 10)), 1);
+        assert_eq!(press(&mut counter, MouseButton::Right, 100, (10, 10)), 1);
+        assert_eq!(press(&mut counter, MouseButton::Right, 200, (10, 10)), 2);
+    }
+
+    #[test]
+    fn click_counter_handles_timestamp_wraparound() {
+        let mut counter = ClickCounter::default();
+
+        assert_eq!(press(&mut counter, MouseButton::Left, u32::MAX - 100, (10, 10)), 1);
+        assert_eq!(press(&mut counter, MouseButton::Left, 200, (10, 10)), 2);
+        assert_eq!(press(&mut counter, MouseButton::Left, 1000, (10, 10)), 1);
+    }
+}
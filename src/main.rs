@@ -9,26 +9,49 @@ fn main() {
         window.poll_messages(|event| {
             match event {
                 WindowEvent::Close => is_running = false,
-                WindowEvent::Resize(width, height) => println!("Window resized: {}, {}", width, height),
+                WindowEvent::Resize { size, .. } => println!("Window resized: {}, {}", size.width, size.height),
+                WindowEvent::Moved(x, y) => println!("Window moved: {}, {}", x, y),
+                WindowEvent::RedrawRequested => println!("Redraw requested."),
+                WindowEvent::Focused(focused) => println!("Window {}.", if focused { "focused" } else { "unfocused" }),
+                WindowEvent::MonitorsChanged => println!("Monitors changed."),
+                WindowEvent::ScaleFactorChanged { scale, .. } => println!("Scale factor changed: {}", scale),
                 WindowEvent::Input(event) => match event {
-                    WindowInputEvent::MouseMove(x, y) => {
+                    WindowInputEvent::MouseMove { x, y, .. } => {
                         println!("Mouse moved!: {}, {}", x, y);
                     },
-                    WindowInputEvent::KeyDown(key) => {
+                    WindowInputEvent::KeyDown { key: Some(key), .. } => {
                         println!("Key pressed: {}", key.as_str());
                     },
-                    WindowInputEvent::KeyUp(key) => {
+                    WindowInputEvent::KeyUp { key: Some(key), .. } => {
                         println!("Key released: {}", key.as_str());
                     },
-                    WindowInputEvent::MouseWheelMove(dz) => {
-                        println!("Mouse wheel {}", if dz > 0 { "up" } else { "down" });
+                    WindowInputEvent::KeyDown { scancode, .. } => {
+                        println!("Key pressed: {:?}", scancode);
                     },
-                    WindowInputEvent::MouseDown(button) => {
+                    WindowInputEvent::KeyUp { scancode, .. } => {
+                        println!("Key released: {:?}", scancode);
+                    },
+                    WindowInputEvent::MouseWheel { delta_x, delta_y, .. } => {
+                        println!("Mouse wheel: {}, {}", delta_x, delta_y);
+                    },
+                    WindowInputEvent::RawMouseMotion { dx, dy } => {
+                        println!("Raw mouse motion: {}, {}", dx, dy);
+                    },
+                    WindowInputEvent::MouseDown { button, .. } => {
                         println!("Mouse {} down.", button.as_str());
                     },
-                    WindowInputEvent::MouseUp(button) => {
+                    WindowInputEvent::MouseUp { button, .. } => {
                         println!("Mouse {} up.", button.as_str());
-                    }
+                    },
+                    WindowInputEvent::Text(text) => {
+                        println!("Text input: {}", text);
+                    },
+                    WindowInputEvent::CursorEntered => {
+                        println!("Cursor entered.");
+                    },
+                    WindowInputEvent::CursorLeft => {
+                        println!("Cursor left.");
+                    },
                 },
             }
         });
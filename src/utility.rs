@@ -10,3 +10,377 @@ pub fn get_x_y_lparam(l_param: LPARAM) -> (i16, i16) {
 pub fn get_wheel_delta_wparam(w_param: WPARAM) -> i16 {
     ((w_param >> 16) & 0xFFFF) as i16
 }
+
+#[cfg(target_os = "windows")]
+pub fn get_xbutton_wparam(w_param: WPARAM) -> u16 {
+    ((w_param >> 16) & 0xFFFF) as u16
+}
+
+/// The `WM_NORMAL_HINTS` window property, see the ICCCM section 4.1.2.3.
+#[cfg(target_os = "linux")]
+#[derive(Default, Clone, Copy)]
+pub struct WmSizeHints {
+    pub position: Option<(i32, i32)>,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+    pub resize_increments: Option<(u32, u32)>,
+    /// The width to height ratio, used as both the minimum and maximum aspect.
+    pub aspect_ratio: Option<(u32, u32)>,
+}
+
+#[cfg(target_os = "linux")]
+impl WmSizeHints {
+    const US_POSITION: u32 = 1 << 0;
+    const P_MIN_SIZE: u32 = 1 << 4;
+    const P_MAX_SIZE: u32 = 1 << 5;
+    const P_RESIZE_INC: u32 = 1 << 6;
+    const P_ASPECT: u32 = 1 << 7;
+
+    /// Returns the hints in the 18 word wire format of the `WM_SIZE_HINTS` type.
+    pub fn to_wire(self) -> [u32; 18] {
+        let mut wire = [0u32; 18];
+
+        if let Some((x, y)) = self.position {
+            wire[0] |= Self::US_POSITION;
+            wire[1] = x as u32;
+            wire[2] = y as u32;
+        }
+
+        if let Some((width, height)) = self.min_size {
+            wire[0] |= Self::P_MIN_SIZE;
+            wire[5] = width;
+            wire[6] = height;
+        }
+
+        if let Some((width, height)) = self.max_size {
+            wire[0] |= Self::P_MAX_SIZE;
+            wire[7] = width;
+            wire[8] = height;
+        }
+
+        if let Some((width, height)) = self.resize_increments {
+            wire[0] |= Self::P_RESIZE_INC;
+            wire[9] = width;
+            wire[10] = height;
+        }
+
+        if let Some((width, height)) = self.aspect_ratio {
+            wire[0] |= Self::P_ASPECT;
+            wire[11] = width;
+            wire[12] = height;
+            wire[13] = width;
+            wire[14] = height;
+        }
+
+        wire
+    }
+}
+
+/// Returns the refresh rate of a RandR mode in millihertz, or 0 if the mode has no timings.
+#[cfg(target_os = "linux")]
+pub fn refresh_rate_millihertz(mode_info: &xcb::randr::ModeInfo) -> u32 {
+    use xcb::randr::ModeFlag;
+
+    let mut vtotal = mode_info.vtotal as u64;
+
+    // A double scanned mode shows every line twice, an interlaced one shows half of the lines per refresh.
+    if mode_info.mode_flags.contains(ModeFlag::DOUBLE_SCAN) {
+        vtotal *= 2;
+    }
+
+    if mode_info.mode_flags.contains(ModeFlag::INTERLACE) {
+        vtotal /= 2;
+    }
+
+    match mode_info.htotal as u64 * vtotal {
+        0 => 0,
+        pixels => (mode_info.dot_clock as u64 * 1000 / pixels) as u32,
+    }
+}
+
+/// Estimates the scale factor of a monitor `pixels` wide and `millimeters` wide, in steps of a quarter. Returns 1.0
+/// for monitors that do not report their size, or report a size no real monitor has.
+#[cfg(target_os = "linux")]
+pub fn scale_factor_from_density(pixels: u32, millimeters: u32) -> f64 {
+    if millimeters == 0 {
+        return 1.0;
+    }
+
+    let dpi = pixels as f64 * 25.4 / millimeters as f64;
+    let scale_factor = (dpi / 96.0 * 4.0).round() / 4.0;
+
+    if (1.0..=4.0).contains(&scale_factor) { scale_factor } else { 1.0 }
+}
+
+/// Returns the value of the resource `name` in `resources`, the contents of the `RESOURCE_MANAGER` property.
+#[cfg(target_os = "linux")]
+pub fn xresource<'a>(resources: &'a str, name: &str) -> Option<&'a str> {
+    resources.lines().find_map(|line| {
+        let (line_name, value) = line.split_once(':')?;
+
+        (line_name.trim() == name).then(|| value.trim())
+    })
+}
+
+/// Returns the integer setting `name` in `data`, the contents of the `_XSETTINGS_SETTINGS` property. See the
+/// XSETTINGS specification for the format.
+#[cfg(target_os = "linux")]
+pub fn xsettings_integer(data: &[u8], name: &str) -> Option<i32> {
+    const TYPE_INTEGER: u8 = 0;
+    const TYPE_STRING: u8 = 1;
+    const TYPE_COLOR: u8 = 2;
+
+    let big_endian = *data.first()? == 1;
+
+    let card16 = |offset: usize| -> Option<u16> {
+        let bytes = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+    };
+
+    let card32 = |offset: usize| -> Option<u32> {
+        let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    };
+
+    let padded = |len: usize| (len + 3) & !3;
+
+    // The byte order and padding are followed by the serial and the number of settings.
+    let count = card32(8)?;
+    let mut offset = 12;
+
+    for _ in 0..count {
+        let setting_type = *data.get(offset)?;
+        let name_len = card16(offset + 2)? as usize;
+        let setting_name = data.get(offset + 4..offset + 4 + name_len)?;
+
+        // Each name is followed by the serial of the last change of the setting, then the value.
+        offset += 4 + padded(name_len) + 4;
+
+        match setting_type {
+            TYPE_INTEGER if setting_name == name.as_bytes() => return Some(card32(offset)? as i32),
+            TYPE_INTEGER => offset += 4,
+            TYPE_STRING => offset += 4 + padded(card32(offset)? as usize),
+            TYPE_COLOR => offset += 8,
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+#[cfg(target_os = "linux")]
+pub fn fp3232_to_f64(value: xcb::xinput::Fp3232) -> f64 {
+    value.integral as f64 + value.frac as f64 / (1u64 << 32) as f64
+}
+
+/// Pairs the numbers of the valuators set in the valuator `mask` of an XInput 2 event with their `values`.
+#[cfg(target_os = "linux")]
+pub fn valuator_values<'a>(
+    mask: &'a [u32],
+    values: &'a [xcb::xinput::Fp3232],
+) -> impl Iterator<Item = (u16, f64)> + 'a {
+    (0..mask.len() as u16 * 32)
+        .filter(|&number| mask[number as usize / 32] & 1 << (number % 32) != 0)
+        .zip(values.iter().map(|&value| fp3232_to_f64(value)))
+}
+
+/// Returns the `_MOTIF_WM_HINTS` window property toggling the window manager decorations.
+#[cfg(target_os = "linux")]
+pub fn motif_wm_hints(decorated: bool) -> [u32; 5] {
+    const MWM_HINTS_DECORATIONS: u32 = 1 << 1;
+
+    [MWM_HINTS_DECORATIONS, 0, decorated as u32, 0, 0]
+}
+
+/// Scancode set 1 codes whose evdev key code differs from the code itself, by extended flag.
+#[cfg(target_os = "windows")]
+const SET1_EVDEV_EXCEPTIONS: &[(u32, bool, u32)] = &[
+    (0x1C, true, 96),   // Keypad Enter
+    (0x1D, true, 97),   // Right Control
+    (0x35, true, 98),   // Keypad Divide
+    (0x37, true, 99),   // Print Screen
+    (0x38, true, 100),  // Right Alt
+    (0x45, false, 119), // Pause, which Windows reports without the extended flag, unlike Num Lock
+    (0x45, true, 69),   // Num Lock
+    (0x47, true, 102),  // Home
+    (0x48, true, 103),  // Up
+    (0x49, true, 104),  // Page Up
+    (0x4B, true, 105),  // Left
+    (0x4D, true, 106),  // Right
+    (0x4F, true, 107),  // End
+    (0x50, true, 108),  // Down
+    (0x51, true, 109),  // Page Down
+    (0x52, true, 110),  // Insert
+    (0x53, true, 111),  // Delete
+    (0x54, false, 99),  // Alt + Print Screen
+    (0x5B, true, 125),  // Left Windows
+    (0x5C, true, 126),  // Right Windows
+    (0x5D, true, 127),  // Menu
+    (0x64, false, 183), // F13
+    (0x65, false, 184), // F14
+    (0x66, false, 185), // F15
+    (0x67, false, 186), // F16
+    (0x68, false, 187), // F17
+    (0x69, false, 188), // F18
+    (0x6A, false, 189), // F19
+    (0x6B, false, 190), // F20
+    (0x6C, false, 191), // F21
+    (0x6D, false, 192), // F22
+    (0x6E, false, 193), // F23
+    (0x76, false, 194), // F24
+    (0x70, false, 93),  // Katakana / Hiragana
+    (0x73, false, 89),  // Ro
+    (0x79, false, 92),  // Henkan
+    (0x7B, false, 94),  // Muhenkan
+    (0x7D, false, 124), // Yen
+    (0x10, true, 165),  // Previous Track
+    (0x19, true, 163),  // Next Track
+    (0x20, true, 113),  // Mute
+    (0x22, true, 164),  // Play / Pause
+    (0x24, true, 166),  // Stop
+    (0x2E, true, 114),  // Volume Down
+    (0x30, true, 115),  // Volume Up
+];
+
+/// Converts a scancode set 1 code to an evdev key code. The codes of the main block of the keyboard coincide.
+#[cfg(target_os = "windows")]
+pub fn evdev_from_set1(scancode: u32, extended: bool) -> u32 {
+    SET1_EVDEV_EXCEPTIONS.iter()
+        .find(|&&(set1, ext, _)| set1 == scancode && ext == extended)
+        .map(|&(_, _, evdev)| evdev)
+        .unwrap_or(scancode)
+}
+
+/// Converts an evdev key code to a scancode set 1 code and extended flag.
+#[cfg(target_os = "windows")]
+pub fn set1_from_evdev(evdev: u32) -> Option<(u32, bool)> {
+    if let Some(&(set1, extended, _)) = SET1_EVDEV_EXCEPTIONS.iter().find(|&&(_, _, code)| code == evdev) {
+        return Some((set1, extended));
+    }
+
+    // Codes that are not exceptions map to themselves, unless the code itself is taken by an exception.
+    (evdev <= 0x7F && evdev_from_set1(evdev, false) == evdev).then_some((evdev, false))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    enum Setting<'a> {
+        Integer(i32),
+        String(&'a str),
+        Color([u16; 4]),
+    }
+
+    /// Serializes `settings` in the format of the `_XSETTINGS_SETTINGS` property.
+    fn xsettings(big_endian: bool, settings: &[(&str, Setting)]) -> Vec<u8> {
+        let card16 = |value: u16| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        let card32 = |value: u32| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        let pad = |data: &mut Vec<u8>| data.resize((data.len() + 3) & !3, 0);
+
+        let mut data = vec![big_endian as u8, 0, 0, 0];
+        data.extend(card32(1));
+        data.extend(card32(settings.len() as u32));
+
+        for (name, setting) in settings {
+            let setting_type = match setting {
+                Setting::Integer(_) => 0,
+                Setting::String(_) => 1,
+                Setting::Color(_) => 2,
+            };
+
+            data.extend([setting_type, 0]);
+            data.extend(card16(name.len() as u16));
+            data.extend(name.as_bytes());
+            pad(&mut data);
+            data.extend(card32(0));
+
+            match setting {
+                Setting::Integer(value) => data.extend(card32(*value as u32)),
+                Setting::String(value) => {
+                    data.extend(card32(value.len() as u32));
+                    data.extend(value.as_bytes());
+                    pad(&mut data);
+                },
+                Setting::Color(channels) => channels.iter().for_each(|&channel| data.extend(card16(channel))),
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn xsettings_integer_reads_both_byte_orders() {
+        for big_endian in [false, true] {
+            let data = xsettings(big_endian, &[
+                ("Xft/DPI", Setting::Integer(147456)),
+                ("Gtk/Offset", Setting::Integer(-2)),
+            ]);
+
+            assert_eq!(xsettings_integer(&data, "Xft/DPI"), Some(147456));
+            assert_eq!(xsettings_integer(&data, "Gtk/Offset"), Some(-2));
+            assert_eq!(xsettings_integer(&data, "Xft/Hinting"), None);
+        }
+    }
+
+    #[test]
+    fn xsettings_integer_skips_string_and_color_entries() {
+        for big_endian in [false, true] {
+            let data = xsettings(big_endian, &[
+                ("Net/ThemeName", Setting::String("Adwaita")),
+                ("Gtk/Color", Setting::Color([1, 2, 3, 4])),
+                ("Xft/DPI", Setting::Integer(98304)),
+            ]);
+
+            assert_eq!(xsettings_integer(&data, "Xft/DPI"), Some(98304));
+            assert_eq!(xsettings_integer(&data, "Net/ThemeName"), None);
+            assert_eq!(xsettings_integer(&data, "Gtk/Color"), None);
+        }
+    }
+
+    #[test]
+    fn xsettings_integer_rejects_truncated_data() {
+        let data = xsettings(false, &[("Net/ThemeName", Setting::String("Adwaita")), ("Xft/DPI", Setting::Integer(1))]);
+
+        for len in 0..data.len() {
+            assert_eq!(xsettings_integer(&data[..len], "Xft/DPI"), None);
+        }
+    }
+
+    fn fp3232(integral: i32, frac: u32) -> xcb::xinput::Fp3232 {
+        xcb::xinput::Fp3232 { integral, frac }
+    }
+
+    #[test]
+    fn fp3232_to_f64_adds_fraction() {
+        assert_eq!(fp3232_to_f64(fp3232(3, 0)), 3.0);
+        assert_eq!(fp3232_to_f64(fp3232(3, 1 << 31)), 3.5);
+        assert_eq!(fp3232_to_f64(fp3232(-2, 1 << 30)), -1.75);
+    }
+
+    #[test]
+    fn valuator_values_pairs_set_bits_with_values() {
+        let mask = [1 << 1 | 1 << 3, 1 << 2];
+        let values = [fp3232(10, 0), fp3232(30, 0), fp3232(-1, 1 << 31)];
+
+        let pairs: Vec<_> = valuator_values(&mask, &values).collect();
+
+        assert_eq!(pairs, [(1, 10.0), (3, 30.0), (34, -0.5)]);
+    }
+
+    #[test]
+    fn valuator_values_handles_empty_mask() {
+        assert_eq!(valuator_values(&[], &[]).count(), 0);
+        assert_eq!(valuator_values(&[0, 0], &[]).count(), 0);
+    }
+
+    #[test]
+    fn xresource_finds_value() {
+        let resources = "Xft.antialias:\t1\n*background: #000000\nXft.dpi:\t144\nXcursor.theme: a:b\n";
+
+        assert_eq!(xresource(resources, "Xft.dpi"), Some("144"));
+        assert_eq!(xresource(resources, "Xcursor.theme"), Some("a:b"));
+        assert_eq!(xresource(resources, "Xft.hinting"), None);
+        assert_eq!(xresource(resources, "background"), None);
+    }
+}
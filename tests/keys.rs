@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+use simple_window::Keys;
+
+#[test]
+fn virtual_keys_round_trip() {
+    for &key in Keys::ALL {
+        assert_eq!(Keys::try_from_usize(key.as_usize()), Some(key), "{}", key.as_str());
+    }
+}
+
+#[test]
+fn all_lists_every_key_once() {
+    let all: HashSet<Keys> = Keys::ALL.iter().copied().collect();
+    assert_eq!(all.len(), Keys::ALL.len());
+
+    for vk in 0..=0xFF {
+        if let Some(key) = Keys::try_from_usize(vk) {
+            assert!(all.contains(&key), "{} is missing from Keys::ALL", key.as_str());
+        }
+    }
+}
+
+#[test]
+fn virtual_keys_cover_digits_and_oem_keys() {
+    for vk in (0x30..=0x39).chain(0xBA..=0xC0).chain(0xDB..=0xDF) {
+        assert!(Keys::try_from_usize(vk).is_some(), "{:#x}", vk);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn keysyms_round_trip() {
+    let mut without_keysym = Vec::new();
+
+    for &key in Keys::ALL {
+        match key.as_keysym() {
+            Some(key_sym) => assert_eq!(Keys::from_keysym(key_sym), Some(key), "{}", key.as_str()),
+            None => without_keysym.push(key),
+        }
+    }
+
+    assert_eq!(
+        without_keysym,
+        [Keys::Shift, Keys::Control, Keys::Accept, Keys::Snapshot, Keys::AbntC1, Keys::AbntC2, Keys::Oem8],
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn keysym_aliases() {
+    use x11::keysym;
+
+    assert_eq!(Keys::from_keysym(keysym::XK_A), Some(Keys::A));
+    assert_eq!(Keys::from_keysym(keysym::XK_equal), Some(Keys::Plus));
+    assert_eq!(Keys::from_keysym(keysym::XK_KP_Enter), Some(Keys::Enter));
+    assert_eq!(Keys::from_keysym(keysym::XK_KP_Home), Some(Keys::Home));
+    assert_eq!(Keys::from_keysym(keysym::XK_ISO_Level3_Shift), Some(Keys::RMenu));
+    assert_eq!(Keys::from_keysym(keysym::XF86XK_AudioPause), Some(Keys::MediaPlayPause));
+}
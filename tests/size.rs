@@ -0,0 +1,12 @@
+use simple_window::{LogicalSize, PhysicalSize};
+
+#[test]
+fn sizes_convert_at_scale_factor() {
+    assert_eq!(PhysicalSize::new(800, 600).to_logical(2.0), LogicalSize::new(400.0, 300.0));
+    assert_eq!(LogicalSize::new(400.0, 300.0).to_physical(1.5), PhysicalSize::new(600, 450));
+}
+
+#[test]
+fn physical_conversion_rounds_to_nearest_pixel() {
+    assert_eq!(LogicalSize::new(100.5, 33.3).to_physical(1.25), PhysicalSize::new(126, 42));
+}
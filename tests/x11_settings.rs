@@ -0,0 +1,97 @@
+//! Tests that change the settings of a running X server, such as Xvfb. Skipped when `DISPLAY` is not set. Kept apart
+//! from the other X11 tests, as the test binaries run one after the other while the tests in one binary run in
+//! parallel.
+#![cfg(target_os = "linux")]
+
+use simple_window::Window;
+use xcb::x;
+
+fn has_display() -> bool {
+    if std::env::var_os("DISPLAY").is_none() {
+        eprintln!("DISPLAY is not set, skipping.");
+        return false;
+    }
+
+    true
+}
+
+/// Puts back the X resources of the root window `root` when dropped, or deletes them if there were none.
+struct RestoreResources<'a> {
+    conn: &'a xcb::Connection,
+    root: x::Window,
+    resources: Option<Vec<u8>>,
+}
+
+impl Drop for RestoreResources<'_> {
+    fn drop(&mut self) {
+        let cookie = match &self.resources {
+            Some(data) => self.conn.send_request_checked(&x::ChangeProperty {
+                mode: x::PropMode::Replace,
+                window: self.root,
+                property: x::ATOM_RESOURCE_MANAGER,
+                r#type: x::ATOM_STRING,
+                data: data.as_slice(),
+            }),
+            None => self.conn.send_request_checked(&x::DeleteProperty {
+                window: self.root,
+                property: x::ATOM_RESOURCE_MANAGER,
+            }),
+        };
+
+        if let Err(e) = self.conn.check_request(cookie) {
+            eprintln!("Failed to restore the X resources: {}", e);
+        }
+    }
+}
+
+#[test]
+fn scale_factor_follows_xft_dpi_resource() {
+    if !has_display() {
+        return;
+    }
+
+    let (conn, screen_num) = xcb::Connection::connect(None).unwrap();
+    let root = conn.get_setup().roots().nth(screen_num as usize).unwrap().root();
+
+    // The Xft/DPI of an XSETTINGS manager takes precedence over the resource.
+    let xsettings_selection = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+        only_if_exists: false,
+        name: format!("_XSETTINGS_S{}", screen_num).as_bytes(),
+    })).unwrap().atom();
+
+    let owner = conn.wait_for_reply(conn.send_request(&x::GetSelectionOwner {
+        selection: xsettings_selection,
+    })).unwrap().owner();
+
+    if owner != x::WINDOW_NONE {
+        eprintln!("An XSETTINGS manager is running, skipping.");
+        return;
+    }
+
+    let reply = conn.wait_for_reply(conn.send_request(&x::GetProperty {
+        delete: false,
+        window: root,
+        property: x::ATOM_RESOURCE_MANAGER,
+        r#type: x::ATOM_STRING,
+        long_offset: 0,
+        long_length: u32::MAX / 4,
+    })).unwrap();
+
+    let _restore = RestoreResources {
+        conn: &conn,
+        root,
+        resources: (reply.r#type() != x::ATOM_NONE).then(|| reply.value::<u8>().to_vec()),
+    };
+
+    conn.send_and_check_request(&x::ChangeProperty {
+        mode: x::PropMode::Replace,
+        window: root,
+        property: x::ATOM_RESOURCE_MANAGER,
+        r#type: x::ATOM_STRING,
+        data: b"Xft.antialias:\t1\nXft.dpi:\t192\n",
+    }).unwrap();
+
+    let window = Window::new("Scale Factor Test", 0, 0, 100, 100);
+
+    assert_eq!(window.scale_factor(), 2.0);
+}
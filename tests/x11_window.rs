@@ -0,0 +1,293 @@
+//! Tests against a running X server, such as Xvfb. Skipped when `DISPLAY` is not set.
+#![cfg(target_os = "linux")]
+
+use std::time::Duration;
+
+use raw_window_handle::RawWindowHandle;
+use simple_window::{PhysicalSize, Window, WindowEvent};
+use xcb::{randr, x, Xid, XidNew};
+
+fn has_display() -> bool {
+    if std::env::var_os("DISPLAY").is_none() {
+        eprintln!("DISPLAY is not set, skipping.");
+        return false;
+    }
+
+    true
+}
+
+fn window_id(window: &Window) -> u32 {
+    match window.raw_window_handle() {
+        RawWindowHandle::Xcb(handle) => handle.window.get(),
+        _ => unreachable!(),
+    }
+}
+
+/// Collects the ids of `window` and all of its descendants.
+fn collect_tree(conn: &xcb::Connection, window: x::Window, ids: &mut Vec<u32>) {
+    ids.push(window.resource_id());
+
+    let reply = conn.wait_for_reply(conn.send_request(&x::QueryTree { window })).unwrap();
+
+    for &child in reply.children() {
+        collect_tree(conn, child, ids);
+    }
+}
+
+fn server_tree() -> Vec<u32> {
+    let (conn, screen_num) = xcb::Connection::connect(None).unwrap();
+    let root = conn.get_setup().roots().nth(screen_num as usize).unwrap().root();
+
+    let mut ids = Vec::new();
+    collect_tree(&conn, root, &mut ids);
+
+    ids
+}
+
+#[test]
+fn drop_destroys_window() {
+    if !has_display() {
+        return;
+    }
+
+    let window = Window::new("Drop Test", 0, 0, 100, 100);
+    let id = window_id(&window);
+
+    assert!(server_tree().contains(&id));
+
+    drop(window);
+
+    assert!(!server_tree().contains(&id));
+}
+
+#[test]
+fn cursor_position_is_relative_to_window() {
+    if !has_display() {
+        return;
+    }
+
+    let window = Window::new("Cursor Test", 0, 0, 100, 100);
+    let x_window = unsafe { x::Window::new(window_id(&window)) };
+
+    let (conn, _) = xcb::Connection::connect(None).unwrap();
+    conn.send_and_check_request(&x::WarpPointer {
+        src_window: x::Window::none(),
+        dst_window: x_window,
+        src_x: 0,
+        src_y: 0,
+        src_width: 0,
+        src_height: 0,
+        dst_x: 30,
+        dst_y: 40,
+    }).unwrap();
+
+    assert_eq!(window.cursor_position(), Some((30, 40)));
+}
+
+#[test]
+fn decorations_surround_client_area() {
+    if !has_display() {
+        return;
+    }
+
+    let window = Window::new("Position Test", 10, 20, 100, 100);
+    let (outer_x, outer_y) = window.outer_position().unwrap();
+    let (inner_x, inner_y) = window.inner_position().unwrap();
+
+    assert!(outer_x <= inner_x && outer_y <= inner_y);
+}
+
+#[test]
+fn size_constraints_update_normal_hints() {
+    if !has_display() {
+        return;
+    }
+
+    let mut window = Window::new("Size Hints Test", 0, 0, 100, 100);
+    window.set_min_inner_size(Some(PhysicalSize::new(50, 60)));
+    window.set_resize_increments(Some(PhysicalSize::new(8, 16)));
+    window.set_aspect_ratio(Some((4, 3)));
+
+    let x_window = unsafe { x::Window::new(window_id(&window)) };
+
+    let (conn, _) = xcb::Connection::connect(None).unwrap();
+    let reply = conn.wait_for_reply(conn.send_request(&x::GetProperty {
+        delete: false,
+        window: x_window,
+        property: x::ATOM_WM_NORMAL_HINTS,
+        r#type: x::ATOM_WM_SIZE_HINTS,
+        long_offset: 0,
+        long_length: 18,
+    })).unwrap();
+    let hints = reply.value::<u32>();
+
+    // PMinSize, PResizeInc and PAspect.
+    assert_eq!(hints[0], 1 << 4 | 1 << 6 | 1 << 7);
+    assert_eq!(&hints[5..7], &[50, 60]);
+    assert_eq!(&hints[9..15], &[8, 16, 4, 3, 4, 3]);
+}
+
+#[test]
+fn redraw_requests_coalesce() {
+    if !has_display() {
+        return;
+    }
+
+    let mut window = Window::new("Redraw Test", 0, 0, 100, 100);
+    window.request_redraw();
+    window.request_redraw();
+
+    let mut redraws = 0;
+    window.poll_messages(|event| if let WindowEvent::RedrawRequested = event { redraws += 1 });
+    assert_eq!(redraws, 1);
+
+    let mut redraws = 0;
+    window.poll_messages(|event| if let WindowEvent::RedrawRequested = event { redraws += 1 });
+    assert!(redraws <= 1);
+}
+
+#[test]
+fn expose_reports_damage() {
+    if !has_display() {
+        return;
+    }
+
+    let mut window = Window::new("Damage Test", 0, 0, 100, 100);
+
+    // The contents of an unmapped window are lost, mapping it again exposes all of it.
+    window.set_visible(false);
+    window.set_visible(true);
+
+    let mut redraws = 0;
+
+    for _ in 0..100 {
+        window.poll_messages(|event| if let WindowEvent::RedrawRequested = event { redraws += 1 });
+
+        if !window.damage().is_empty() {
+            break;
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(redraws >= 1);
+    assert!(!window.damage().is_empty());
+    assert!(window.damage().iter().all(|rect| rect.x >= 0 && rect.y >= 0
+        && rect.x as u32 + rect.width <= 100 && rect.y as u32 + rect.height <= 100));
+}
+
+#[test]
+fn set_title_writes_utf8_name() {
+    if !has_display() {
+        return;
+    }
+
+    let window = Window::new("Title Test", 0, 0, 100, 100);
+    window.set_title("Größe ✓");
+
+    let x_window = unsafe { x::Window::new(window_id(&window)) };
+
+    let (conn, _) = xcb::Connection::connect(None).unwrap();
+    let net_wm_name = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+        only_if_exists: true,
+        name: b"_NET_WM_NAME",
+    })).unwrap().atom();
+
+    let get_property = |property| conn.wait_for_reply(conn.send_request(&x::GetProperty {
+        delete: false,
+        window: x_window,
+        property,
+        r#type: x::ATOM_ANY,
+        long_offset: 0,
+        long_length: 64,
+    })).unwrap();
+
+    assert_eq!(get_property(net_wm_name).value::<u8>(), "Größe ✓".as_bytes());
+    // WM_NAME is Latin-1, which has no check mark.
+    assert_eq!(get_property(x::ATOM_WM_NAME).value::<u8>(), b"Gr\xf6\xdfe ?");
+}
+
+#[test]
+fn current_monitor_lists_its_video_mode() {
+    if !has_display() {
+        return;
+    }
+
+    let window = Window::new("Monitor Test", 0, 0, 100, 100);
+    let monitor = window.current_monitor().unwrap();
+
+    assert!(monitor.video_modes().iter().any(|video_mode| video_mode.size() == monitor.size()));
+}
+
+#[test]
+fn available_monitors_include_user_defined_monitor() {
+    if !has_display() {
+        return;
+    }
+
+    let (conn, screen_num) = xcb::Connection::connect_with_extensions(None, &[xcb::Extension::RandR], &[]).unwrap();
+    let root = conn.get_setup().roots().nth(screen_num as usize).unwrap().root();
+
+    conn.wait_for_reply(conn.send_request(&randr::QueryVersion { major_version: 1, minor_version: 5 })).unwrap();
+
+    let name = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+        only_if_exists: false,
+        name: b"SIMPLE-WINDOW-TEST",
+    })).unwrap().atom();
+
+    // Like `xrandr --setmonitor SIMPLE-WINDOW-TEST 200/53x100/26+0+0 none`.
+    let monitor_info = randr::MonitorInfoBuf::new(name, false, false, 0, 0, 200, 100, 53, 26, &[]);
+    conn.send_and_check_request(&randr::SetMonitor { window: root, monitorinfo: &monitor_info }).unwrap();
+
+    let window = Window::new("Monitor Test", 0, 0, 100, 100);
+    let monitors = window.available_monitors();
+
+    conn.send_and_check_request(&randr::DeleteMonitor { window: root, name }).unwrap();
+
+    let monitor = monitors.iter().find(|monitor| monitor.name() == "SIMPLE-WINDOW-TEST").unwrap();
+
+    assert_eq!(monitor.position(), (0, 0));
+    assert_eq!(monitor.size(), (200, 100));
+    assert_eq!(monitor.size_mm(), (53, 26));
+    assert!(monitor.video_modes().is_empty());
+}
+
+#[test]
+fn user_defined_monitor_reports_monitors_changed() {
+    if !has_display() {
+        return;
+    }
+
+    let mut window = Window::new("Monitors Changed Test", 0, 0, 100, 100);
+    window.poll_messages(|_| {});
+
+    let (conn, screen_num) = xcb::Connection::connect_with_extensions(None, &[xcb::Extension::RandR], &[]).unwrap();
+    let root = conn.get_setup().roots().nth(screen_num as usize).unwrap().root();
+
+    conn.wait_for_reply(conn.send_request(&randr::QueryVersion { major_version: 1, minor_version: 5 })).unwrap();
+
+    let name = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+        only_if_exists: false,
+        name: b"SIMPLE-WINDOW-CHANGE-TEST",
+    })).unwrap().atom();
+
+    let monitor_info = randr::MonitorInfoBuf::new(name, false, false, 0, 0, 200, 100, 53, 26, &[]);
+    conn.send_and_check_request(&randr::SetMonitor { window: root, monitorinfo: &monitor_info }).unwrap();
+
+    let mut changes = 0;
+
+    for _ in 0..100 {
+        window.poll_messages(|event| if let WindowEvent::MonitorsChanged = event { changes += 1 });
+
+        if changes > 0 {
+            break;
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    conn.send_and_check_request(&randr::DeleteMonitor { window: root, name }).unwrap();
+
+    // The change arrives as several events, reported once.
+    assert_eq!(changes, 1);
+}